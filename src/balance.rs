@@ -0,0 +1,119 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::BoxedFuture,
+};
+
+use crate::economy::Balance;
+
+const BALANCE_PATH : &str = "balance.ron";
+// how often to check balance.ron for edits
+const BALANCE_POLL_SECS : f32 = 1.0;
+
+// the balance the game is played with. stays at the built-in defaults until
+// balance.ron loads, and whenever it's missing or invalid.
+#[derive(Resource, Default, Deref)]
+pub struct BalanceConfig(pub Balance);
+
+#[derive(Asset, TypePath)]
+pub struct BalanceAsset(Balance);
+
+#[derive(Default)]
+struct BalanceLoader;
+
+impl AssetLoader for BalanceLoader {
+    type Asset = BalanceAsset;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<BalanceAsset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(BalanceAsset(ron::de::from_bytes(&bytes)?))
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["balance.ron"]
+    }
+}
+
+// keeps balance.ron loaded for as long as the game runs
+#[derive(Resource)]
+struct BalanceHandle(Handle<BalanceAsset>);
+
+pub struct BalancePlugin;
+
+impl Plugin for BalancePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<BalanceAsset>()
+            .init_asset_loader::<BalanceLoader>()
+            .init_resource::<BalanceConfig>()
+            .add_systems(Startup, load_balance)
+            .add_systems(PreUpdate, apply_balance_system);
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(Update, watch_balance_system);
+    }
+}
+
+fn load_balance(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(BalanceHandle(asset_server.load(BALANCE_PATH)));
+}
+
+// bevy's own file watching needs the file_watcher feature and its notify
+// dependency, so poll the file's modified time and ask for a reload instead
+#[cfg(not(target_arch = "wasm32"))]
+fn watch_balance_system(
+    asset_server: Res<AssetServer>,
+    handle: Res<BalanceHandle>,
+    mut poll: Local<Option<Timer>>,
+    mut last_modified: Local<Option<std::time::SystemTime>>,
+    time: Res<Time>,
+) {
+    let poll = poll.get_or_insert_with(|| Timer::from_seconds(BALANCE_POLL_SECS, TimerMode::Repeating));
+    if !poll.tick(time.delta()).just_finished() {
+        return;
+    }
+    let path = bevy::asset::io::file::FileAssetReader::get_base_path().join("assets").join(BALANCE_PATH);
+    let Ok(modified) = std::fs::metadata(path).and_then(|metadata| metadata.modified()) else {
+        return;
+    };
+    // the first poll just records the time the startup load saw
+    if last_modified.replace(modified).is_some_and(|last| last != modified) {
+        info!("{} changed, reloading", BALANCE_PATH);
+        asset_server.reload(handle.0.path().cloned().unwrap_or_else(|| BALANCE_PATH.into()));
+    }
+}
+
+// a reload that fails to parse never reaches Assets, and one that parses but
+// doesn't validate is skipped here, so the last good balance stays in play
+fn apply_balance_system(
+    mut events: EventReader<AssetEvent<BalanceAsset>>,
+    assets: Res<Assets<BalanceAsset>>,
+    mut config: ResMut<BalanceConfig>,
+) {
+    for event in events.read() {
+        let (AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }) = event else {
+            continue;
+        };
+        let Some(BalanceAsset(balance)) = assets.get(*id) else {
+            continue;
+        };
+        if *balance == config.0 {
+            continue;
+        }
+        match balance.validate() {
+            Ok(()) => {
+                info!("applied {}", BALANCE_PATH);
+                config.0 = balance.clone();
+            }
+            Err(err) => warn!("ignoring invalid {}, keeping the last good balance: {:?}", BALANCE_PATH, err),
+        }
+    }
+}
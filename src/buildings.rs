@@ -0,0 +1,554 @@
+use bevy::{asset::LoadState, prelude::*};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    balance::BalanceConfig,
+    locale::Locale,
+    particles::{EmitterKind, EmitterSites, ParticleTheme, ReadableImage, SparksRequested},
+    resources::{ClickSource, ClicksEmitted, Score, Settings},
+    states::State,
+    ui::PlayerInput,
+};
+
+// the skyline, and the camera looking at it
+pub struct BuildingsPlugin;
+
+impl Plugin for BuildingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScreenShake>()
+            .add_systems(Update, (
+                sync_buildings,
+                update_loading,
+                particle_mode_system.before(update_loading),
+                camera_control_system,
+                minimap_system,
+                shake_on_clicks_system,
+            ).run_if(in_state(State::Game)))
+            .add_systems(Update, building_click_system.in_set(PlayerInput).run_if(in_state(State::Game)))
+            .add_systems(PreUpdate, unshake_camera_system.run_if(in_state(State::Game)))
+            .add_systems(
+                PostUpdate,
+                screen_shake_system
+                    .before(bevy::transform::TransformSystem::TransformPropagate)
+                    .run_if(in_state(State::Game)),
+            );
+    }
+}
+
+pub fn update_loading(
+    query: Query<(Entity, &Building), With<Loading>>,
+    asset_server: ResMut<AssetServer>,
+    images: Res<Assets<Image>>,
+    settings: Res<Settings>,
+    mut commands: Commands,
+    // pixel colors that don't mean any emitter, already warned about
+    mut unknown: Local<Vec<Vec<u8>>>,
+) {
+    let building = asset_server.load("building.png");
+    if asset_server.get_load_state(&building) == Some(LoadState::Failed) {
+        // no image to place particles on, or to draw. a plain block stands
+        // in so the skyline still shows what was bought.
+        for (entity, building) in &query {
+            commands.entity(entity)
+                .remove::<Loading>()
+                .insert(EmitterSites { sites: Vec::new(), theme: ParticleTheme::for_building(building.0) })
+                .with_children(|parent| {
+                    parent.spawn(SpriteBundle {
+                        sprite: Sprite { color: PLACEHOLDER_COLOR, custom_size: Some(BUILDING_CELL), ..default() },
+                        ..default()
+                    });
+                });
+        }
+        return;
+    }
+    let image = images.get(building.clone());
+    if image.is_none() {
+        return;
+    }
+    let image = image.unwrap();
+    let ri = match ReadableImage::new(image) {
+        Ok(ri) => ri,
+        Err(err) => {
+            error!("can't read building.png for particle placement: {:?}", err);
+            for (entity, _) in &query {
+                commands.entity(entity).remove::<Loading>();
+            }
+            return;
+        }
+    };
+    let atlas = TextureAtlas::from_grid(building.clone(), BUILDING_CELL, 2, 1, None, None);
+    let mut sites = Vec::new();
+    if settings.simple_particles {
+        // with_nonzero centers the lit half of the sprite on the origin
+        sites.push((Vec3::new(0.0, 0.0, 1.0), EmitterKind::FacingCamera));
+    } else {
+        ri.with_nonzero(atlas.textures[1], |x, y, pixel| {
+            if pixel.first() == Some(&255) {
+                sites.push((Vec3::new(x, y, 1.0), EmitterKind::FacingCamera));
+            } else if pixel.get(1) == Some(&255) {
+                sites.push((Vec3::new(x, y, 1.0), EmitterKind::FacingLeft));
+            } else if !unknown.iter().any(|known| known == pixel) {
+                warn!("building.png has a pixel {:?} that isn't an emitter color", pixel);
+                unknown.push(pixel.to_vec());
+            }
+        });
+    }
+    for (entity, building) in &query {
+        let theme = ParticleTheme::for_building(building.0);
+        commands.entity(entity).remove::<Loading>().insert(EmitterSites { sites: sites.clone(), theme });
+    }
+}
+
+#[derive(Component)]
+pub struct Loading;
+
+// switching between simple and lit-pixel particles places every building's
+// emitters again. placeholder blocks have none either way, so they're left be.
+pub fn particle_mode_system(
+    settings: Res<Settings>,
+    buildings: Query<(Entity, &EmitterSites), With<Building>>,
+    mut simple: Local<Option<bool>>,
+    mut commands: Commands,
+) {
+    if simple.replace(settings.simple_particles).is_none_or(|was| was == settings.simple_particles) {
+        return;
+    }
+    for (entity, sites) in &buildings {
+        if !sites.sites.is_empty() {
+            commands.entity(entity).insert(Loading);
+        }
+    }
+}
+
+// drawn in place of a building whose image failed to load
+pub const PLACEHOLDER_COLOR : Color = Color::rgb(0.35, 0.35, 0.4);
+
+// which building this is, counting from the first one built
+#[derive(Component)]
+pub struct Building(pub usize);
+
+// matches the spacing sync_buildings lays buildings out at
+pub const BUILDING_SPACING : f32 = 200.0;
+// buildings past this many aren't drawn, just counted on a label after the
+// last one. the economy still uses the real count.
+pub const MAX_VISIBLE_BUILDINGS : u32 = 10;
+// one frame of building.png, before the sprite's scale
+pub const BUILDING_CELL : Vec2 = Vec2::new(61.0, 97.0);
+pub const CAMERA_MIN_SCALE : f32 = 0.25;
+pub const CAMERA_MAX_SCALE : f32 = 8.0;
+// how far past the outermost buildings the camera may wander
+pub const CAMERA_PAN_MARGIN : f32 = 600.0;
+pub const CAMERA_ZOOM_STEP : f32 = 0.1;
+
+// places along the skyline in use: the drawn buildings, plus one for the
+// label when some are hidden
+pub fn skyline_slots(buildings: u32) -> u32 {
+    if buildings > MAX_VISIBLE_BUILDINGS { MAX_VISIBLE_BUILDINGS + 1 } else { buildings }
+}
+
+// a view that fits every building across the window, never zoomed in past 1:1
+pub fn framed_view(buildings: u32, window_width: f32) -> (Vec2, f32) {
+    let span = BUILDING_SPACING * buildings.saturating_sub(1) as f32;
+    let scale = ((span + CAMERA_PAN_MARGIN) / window_width.max(1.0)).clamp(1.0, CAMERA_MAX_SCALE);
+    (Vec2::new(-span / 2.0, 0.0), scale)
+}
+
+// logical pixels
+pub const MINIMAP_SIZE : egui::Vec2 = egui::vec2(220.0, 36.0);
+pub const MINIMAP_DOT_RADIUS : f32 = 3.0;
+
+// the world x range the minimap covers: every building, and the camera's view
+pub fn minimap_span(buildings: impl IntoIterator<Item = f32>, view: (f32, f32)) -> (f32, f32) {
+    let half = BUILDING_SPACING / 2.0;
+    buildings.into_iter().fold(view, |(min, max), x| (min.min(x - half), max.max(x + half)))
+}
+
+// 0-1 across the minimap
+pub fn minimap_fraction(x: f32, span: (f32, f32)) -> f32 {
+    (x - span.0) / (span.1 - span.0).max(1.0)
+}
+
+// clicks needed, in powers of ten, for a single burst to max out trauma
+pub const SHAKE_FULL_TRAUMA_LOG10 : f64 = 24.0;
+pub const SHAKE_DECAY_PER_SECOND : f32 = 1.0;
+// world units at full trauma and intensity, before zoom
+pub const SHAKE_MAX_OFFSET : f32 = 12.0;
+// radians
+pub const SHAKE_MAX_ROTATION : f32 = 0.03;
+// how fast the noise wanders
+pub const SHAKE_FREQUENCY : f32 = 15.0;
+
+#[derive(Resource, Default)]
+pub struct ScreenShake {
+    // 0-1, the shake is trauma squared so small bumps stay subtle
+    trauma: f32,
+    // what the last frame added to the camera, so it can be taken back off
+    applied: Option<(Vec2, f32)>,
+}
+
+impl ScreenShake {
+    fn add(&mut self, clicks: u64) {
+        let trauma = ((clicks as f64 + 1.0).log10() / SHAKE_FULL_TRAUMA_LOG10) as f32;
+        self.trauma = (self.trauma + trauma).min(1.0);
+    }
+
+    // offset and rotation at time `t`, never past the max offset and rotation
+    fn offset(&self, t: f32, intensity: f32) -> (Vec2, f32) {
+        let strength = self.trauma.clamp(0.0, 1.0).powi(2) * intensity.clamp(0.0, 1.0);
+        let t = t * SHAKE_FREQUENCY;
+        (
+            Vec2::new(smooth_noise(t, 0.0), smooth_noise(t, 1.0)) * SHAKE_MAX_OFFSET * strength,
+            smooth_noise(t, 2.0) * SHAKE_MAX_ROTATION * strength,
+        )
+    }
+}
+
+// a few detuned sines, wanders smoothly within -1..=1
+pub fn smooth_noise(t: f32, seed: f32) -> f32 {
+    let seed = seed * 17.31;
+    ((t + seed).sin() + 0.5 * (t * 2.13 + seed * 1.7).sin() + 0.25 * (t * 4.37 + seed * 2.9).sin()) / 1.75
+}
+
+pub fn shake_on_clicks_system(mut clicker_events: EventReader<ClicksEmitted>, mut shake: ResMut<ScreenShake>) {
+    for ClicksEmitted { amount, .. } in clicker_events.read() {
+        shake.add(*amount);
+    }
+}
+
+// takes last frame's shake back off the camera before anything else moves it
+pub fn unshake_camera_system(
+    mut cameras: Query<&mut Transform, With<Camera2d>>,
+    mut shake: ResMut<ScreenShake>,
+) {
+    let Some((offset, _)) = shake.applied.take() else {
+        return;
+    };
+    if let Ok(mut transform) = cameras.get_single_mut() {
+        transform.translation -= offset.extend(0.0);
+        transform.rotation = Quat::IDENTITY;
+    }
+}
+
+pub fn screen_shake_system(
+    mut cameras: Query<(&mut Transform, &OrthographicProjection), With<Camera2d>>,
+    mut shake: ResMut<ScreenShake>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    shake.trauma = (shake.trauma - SHAKE_DECAY_PER_SECOND * time.delta_seconds()).max(0.0);
+    if !settings.screen_shake || settings.reduce_motion || shake.trauma <= 0.0 {
+        return;
+    }
+    let Ok((mut transform, projection)) = cameras.get_single_mut() else {
+        return;
+    };
+    let (offset, rotation) = shake.offset(time.elapsed_seconds(), settings.shake_intensity);
+    // shake the same amount on screen however far we're zoomed out
+    let offset = offset * projection.scale;
+    transform.translation += offset.extend(0.0);
+    transform.rotation = Quat::from_rotation_z(rotation);
+    shake.applied = Some((offset, rotation));
+}
+
+// wheel zooms, middle-drag pans, unless egui is using the pointer
+#[allow(clippy::too_many_arguments)]
+pub fn camera_control_system(
+    mut cameras: Query<(&mut Transform, &mut OrthographicProjection), With<Camera2d>>,
+    windows: Query<&Window, With<bevy::window::PrimaryWindow>>,
+    mut scroll_events: EventReader<bevy::input::mouse::MouseWheel>,
+    mut motion_events: EventReader<bevy::input::mouse::MouseMotion>,
+    buttons: Res<Input<MouseButton>>,
+    score: Res<Score>,
+    mut contexts: EguiContexts,
+    locale: Res<Locale>,
+) {
+    let Ok((mut transform, mut projection)) = cameras.get_single_mut() else {
+        return;
+    };
+    let ctx = contexts.ctx_mut();
+    let recenter = egui::Area::new("camera_controls")
+        .anchor(egui::Align2::LEFT_BOTTOM, egui::vec2(10.0, -10.0))
+        .show(ctx, |ui| ui.button(locale.t("camera.recenter")).clicked())
+        .inner;
+    if recenter {
+        let width = windows.get_single().map_or(1280.0, |window| window.width());
+        let (center, scale) = framed_view(skyline_slots(score.buildings), width);
+        transform.translation = center.extend(transform.translation.z);
+        projection.scale = scale;
+    }
+
+    let pointer_free = !ctx.wants_pointer_input() && !ctx.is_pointer_over_area();
+    let scrolled: f32 = scroll_events.read().map(|event| event.y).sum();
+    let dragged: Vec2 = motion_events.read().map(|event| event.delta).sum();
+    if !pointer_free {
+        return;
+    }
+    if scrolled != 0.0 {
+        let scale = projection.scale * (1.0 - CAMERA_ZOOM_STEP * scrolled.signum());
+        projection.scale = scale.clamp(CAMERA_MIN_SCALE, CAMERA_MAX_SCALE);
+    }
+    if buttons.pressed(MouseButton::Middle) && dragged != Vec2::ZERO {
+        // screen y grows downward, world y grows upward
+        transform.translation.x -= dragged.x * projection.scale;
+        transform.translation.y += dragged.y * projection.scale;
+    }
+    let leftmost = -BUILDING_SPACING * skyline_slots(score.buildings).saturating_sub(1) as f32 - CAMERA_PAN_MARGIN;
+    transform.translation.x = transform.translation.x.clamp(leftmost, CAMERA_PAN_MARGIN);
+    transform.translation.y = transform.translation.y.clamp(-CAMERA_PAN_MARGIN, CAMERA_PAN_MARGIN);
+}
+
+// every building as a dot, with the camera's view boxed around the ones on
+// screen. clicking recenters the camera on the nearest building.
+#[allow(clippy::type_complexity)]
+pub fn minimap_system(
+    mut cameras: Query<(&mut Transform, &OrthographicProjection), (With<Camera2d>, Without<Building>)>,
+    buildings: Query<&Transform, (With<Building>, Without<Camera2d>)>,
+    mut contexts: EguiContexts,
+    settings: Res<Settings>,
+) {
+    if !settings.minimap {
+        return;
+    }
+    let Ok((mut transform, projection)) = cameras.get_single_mut() else {
+        return;
+    };
+    let xs: Vec<f32> = buildings.iter().map(|building| building.translation.x).collect();
+    let camera_x = transform.translation.x;
+    let view = (camera_x + projection.area.min.x, camera_x + projection.area.max.x);
+    let span = minimap_span(xs.iter().copied(), view);
+    egui::Area::new("minimap")
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-10.0, -10.0))
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                let (response, painter) = ui.allocate_painter(MINIMAP_SIZE, egui::Sense::click());
+                let rect = response.rect;
+                let at = |x: f32| rect.left() + minimap_fraction(x, span) * rect.width();
+                for x in &xs {
+                    painter.circle_filled(egui::pos2(at(*x), rect.center().y), MINIMAP_DOT_RADIUS, ui.visuals().text_color());
+                }
+                let boxed = egui::Rect::from_x_y_ranges(at(view.0)..=at(view.1), rect.y_range());
+                painter.rect_stroke(boxed, 2.0, ui.visuals().selection.stroke);
+                let Some(pointer) = response.interact_pointer_pos().filter(|_| response.clicked()) else {
+                    return;
+                };
+                let clicked_x = span.0 + (pointer.x - rect.left()) / rect.width() * (span.1 - span.0);
+                // unshake_camera_system took last frame's shake off in
+                // PreUpdate, so this is the camera's resting place and the
+                // next shake goes on top of it
+                if let Some(nearest) = xs.iter().min_by(|a, b| (*a - clicked_x).abs().total_cmp(&(*b - clicked_x).abs())) {
+                    transform.translation.x = *nearest;
+                }
+            });
+        });
+}
+
+// the index of the front-most building whose sprite covers `point`.
+// neighbours overlap, and later buildings are drawn on top.
+pub fn building_at(point: Vec2, buildings: impl IntoIterator<Item = (usize, Transform)>) -> Option<usize> {
+    buildings
+        .into_iter()
+        .filter(|(_, transform)| {
+            let local = transform.compute_matrix().inverse().transform_point3(point.extend(transform.translation.z));
+            local.x.abs() <= BUILDING_CELL.x / 2.0 && local.y.abs() <= BUILDING_CELL.y / 2.0
+        })
+        .max_by(|(_, a), (_, b)| a.translation.z.total_cmp(&b.translation.z))
+        .map(|(index, _)| index)
+}
+
+// clicking a building pays out by its tier, unless egui is using the pointer
+#[allow(clippy::too_many_arguments)]
+pub fn building_click_system(
+    buttons: Res<Input<MouseButton>>,
+    windows: Query<&Window, With<bevy::window::PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    buildings: Query<(&Building, &Transform)>,
+    mut contexts: EguiContexts,
+    score: Res<Score>,
+    balance: Res<BalanceConfig>,
+    mut clicker_events: EventWriter<ClicksEmitted>,
+    mut spark_events: EventWriter<SparksRequested>,
+) {
+    if !buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let ctx = contexts.ctx_mut();
+    if ctx.wants_pointer_input() || ctx.is_pointer_over_area() {
+        return;
+    }
+    let (Ok(window), Ok((camera, camera_transform))) = (windows.get_single(), cameras.get_single()) else {
+        return;
+    };
+    let Some(point) = window.cursor_position().and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor)) else {
+        return;
+    };
+    let Some(index) = building_at(point, buildings.iter().map(|(building, transform)| (building.0, *transform))) else {
+        return;
+    };
+    clicker_events.send(ClicksEmitted { amount: score.building_click(&balance, index), source: ClickSource::Manual, hand: None });
+    spark_events.send(SparksRequested { position: point.extend(100.0), theme: ParticleTheme::for_building(index) });
+}
+
+// stands in for the buildings past MAX_VISIBLE_BUILDINGS
+#[derive(Component)]
+pub struct HiddenBuildingsLabel;
+
+// each new building goes through update_loading, so a big jump in buildings
+// is spread over a few frames instead of spawned all at once
+pub const BUILDINGS_SPAWNED_PER_FRAME : usize = 1;
+
+pub fn sync_buildings(
+    query: Query<Entity, With<Building>>,
+    mut labels: Query<(Entity, &mut Text), With<HiddenBuildingsLabel>>,
+    score: Res<Score>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut atlases: ResMut<Assets<TextureAtlas>>,
+    locale: Res<Locale>,
+) {
+    // add buildings
+    let building = asset_server.load("building.png");
+    let atlas_handle = TextureAtlas::from_grid(building.clone(), BUILDING_CELL, 2, 1, None, None);
+    let atlas = atlases.add(atlas_handle);
+
+    let existing = query.iter().count();
+    let visible = score.buildings.min(MAX_VISIBLE_BUILDINGS) as usize;
+    let missing = visible.saturating_sub(existing).min(BUILDINGS_SPAWNED_PER_FRAME);
+
+    for x_idx in existing..existing + missing {
+        commands.spawn((Loading, Building(x_idx), SpriteSheetBundle {
+            texture_atlas: atlas.clone(),
+            transform: Transform::from_xyz(-BUILDING_SPACING * x_idx as f32, -50.0_f32, 0.5 + x_idx as f32 / 10.0f32).with_scale(Vec3::splat(4.0)),
+            ..SpriteSheetBundle::default()
+        }));
+    }
+
+    let hidden = score.buildings.saturating_sub(MAX_VISIBLE_BUILDINGS);
+    let text = locale.tf("buildings.hidden", &[("count", &locale.language.group_digits(hidden as u64))]);
+    match labels.get_single_mut() {
+        Ok((entity, _)) if hidden == 0 => commands.entity(entity).despawn(),
+        Ok((_, mut label)) => {
+            if label.sections[0].value != text {
+                label.sections[0].value = text;
+            }
+        }
+        Err(_) if hidden > 0 => {
+            commands.spawn((HiddenBuildingsLabel, Text2dBundle {
+                text: Text::from_section(text, TextStyle { font_size: 48.0, ..default() }),
+                transform: Transform::from_xyz(-BUILDING_SPACING * MAX_VISIBLE_BUILDINGS as f32, -50.0, 5.0),
+                ..default()
+            }));
+        }
+        Err(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn framed_view_fits_every_building() {
+        assert_eq!(framed_view(1, 1280.0), (Vec2::ZERO, 1.0));
+        let (center, scale) = framed_view(11, 1000.0);
+        assert_eq!(center, Vec2::new(-1000.0, 0.0));
+        assert_eq!(scale, 2.6);
+        assert_eq!(framed_view(1000, 1000.0).1, CAMERA_MAX_SCALE);
+    }
+
+    #[test]
+    fn missing_buildings_arrive_a_few_per_frame() {
+        let mut app = crate::testing::test_app();
+        app.init_asset::<TextureAtlas>()
+            .init_resource::<Locale>()
+            .add_systems(Update, sync_buildings);
+        app.world.resource_mut::<Score>().buildings = 5;
+        let count = |app: &mut App| app.world.query_filtered::<(), With<Building>>().iter(&app.world).count();
+
+        app.update();
+        assert_eq!(count(&mut app), BUILDINGS_SPAWNED_PER_FRAME);
+        for _ in 0..5 {
+            app.update();
+        }
+        assert_eq!(count(&mut app), 5);
+        // and it stops there
+        app.update();
+        assert_eq!(count(&mut app), 5);
+    }
+
+    #[test]
+    fn skyline_stops_growing_at_the_cap() {
+        assert_eq!(skyline_slots(1), 1);
+        assert_eq!(skyline_slots(MAX_VISIBLE_BUILDINGS), MAX_VISIBLE_BUILDINGS);
+        assert_eq!(skyline_slots(MAX_VISIBLE_BUILDINGS + 1), MAX_VISIBLE_BUILDINGS + 1);
+        assert_eq!(skyline_slots(u32::MAX), MAX_VISIBLE_BUILDINGS + 1);
+    }
+
+    #[test]
+    fn minimap_covers_the_buildings_and_the_view() {
+        let view = (-640.0, 640.0);
+        assert_eq!(minimap_span([0.0], view), view);
+        let buildings = [0.0, -BUILDING_SPACING, -2000.0];
+        let span = minimap_span(buildings, view);
+        assert_eq!(span, (-2000.0 - BUILDING_SPACING / 2.0, 640.0));
+        assert_eq!(minimap_fraction(span.0, span), 0.0);
+        assert_eq!(minimap_fraction(span.1, span), 1.0);
+        assert!(buildings.iter().all(|x| (0.0..=1.0).contains(&minimap_fraction(*x, span))));
+    }
+
+    #[test]
+    fn clicks_hit_the_front_most_building() {
+        let at = |index: usize| {
+            let transform = Transform::from_xyz(-BUILDING_SPACING * index as f32, -50.0, 0.5 + index as f32 / 10.0)
+                .with_scale(Vec3::splat(4.0));
+            (index, transform)
+        };
+        let buildings = [at(0), at(1), at(2)];
+        assert_eq!(building_at(Vec2::new(0.0, -50.0), buildings), Some(0));
+        // the first two overlap between their centers, the second is in front
+        assert_eq!(building_at(Vec2::new(-100.0, 0.0), buildings), Some(1));
+        assert_eq!(building_at(Vec2::new(0.0, 500.0), buildings), None);
+        assert_eq!(building_at(Vec2::new(200.0, -50.0), buildings), None);
+    }
+
+    #[test]
+    fn cameras_moved_mid_shake_come_to_rest_where_they_were_put() {
+        let mut app = crate::testing::test_app();
+        app.init_resource::<ScreenShake>()
+            .insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(std::time::Duration::from_millis(100)))
+            .add_systems(PreUpdate, unshake_camera_system)
+            .add_systems(PostUpdate, screen_shake_system);
+        let camera = app.world.spawn((Camera2d::default(), Transform::default(), OrthographicProjection::default())).id();
+        app.world.resource_mut::<ScreenShake>().trauma = 1.0;
+        app.update();
+        app.update();
+        assert!(app.world.resource::<ScreenShake>().applied.is_some());
+
+        // the minimap's recentering, in the middle of the shake
+        let recenter = |mut cameras: Query<&mut Transform, With<Camera2d>>| cameras.single_mut().translation.x = 300.0;
+        app.add_systems(Update, recenter.run_if(run_once()));
+        app.update();
+        // let the shake die down
+        app.world.resource_mut::<ScreenShake>().trauma = 0.0;
+        app.update();
+        assert!(app.world.resource::<ScreenShake>().applied.is_none());
+        assert_eq!(app.world.get::<Transform>(camera).map(|transform| transform.translation.x), Some(300.0));
+    }
+
+    #[test]
+    fn shake_stays_within_bounds() {
+        let mut shake = ScreenShake::default();
+        assert_eq!(shake.offset(1.0, 1.0), (Vec2::ZERO, 0.0));
+        shake.add(1_000);
+        let small = shake.trauma;
+        for _ in 0..100 {
+            shake.add(u64::MAX);
+        }
+        assert!(small > 0.0 && small < shake.trauma);
+        assert_eq!(shake.trauma, 1.0);
+        for step in 0..1000 {
+            let (offset, rotation) = shake.offset(step as f32 * 0.01, 1.0);
+            assert!(offset.x.abs() <= SHAKE_MAX_OFFSET && offset.y.abs() <= SHAKE_MAX_OFFSET);
+            assert!(rotation.abs() <= SHAKE_MAX_ROTATION);
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::locale::Locale;
+
+#[derive(Component, Default, Clone, Copy, Serialize, Deserialize)]
+pub enum HandState {
+    #[default]
+    Filling,
+    Combined,
+    Autoed,
+}
+
+#[derive(Component, Default)]
+pub struct HandLabel;
+
+#[derive(Component, Default)]
+pub struct HandName(pub String);
+
+pub fn default_hand_name(locale: &Locale, index: usize) -> String {
+    locale.tf("hand.default_name", &[("number", &(index + 1))])
+}
+
+// the number in a default name the player hasn't changed
+pub fn default_hand_number(locale: &Locale, name: &str) -> Option<usize> {
+    let template = locale.t("hand.default_name");
+    let (prefix, suffix) = template.split_once("{number}")?;
+    name.strip_prefix(prefix)?.strip_suffix(suffix)?.parse().ok()
+}
+
+// buys fingers for a filling hand on its own until it holds `cap` of them
+#[derive(Component, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoBuy {
+    pub enabled: bool,
+    pub cap: u32,
+}
+
+impl Default for AutoBuy {
+    fn default() -> Self {
+        AutoBuy { enabled: false, cap: 10 }
+    }
+}
+
+// a finger left ready this long is wasting its hand's time
+pub const IDLE_FINGER_SECS : f32 = 5.0;
+
+#[derive(Component, Default)]
+pub struct HandStats {
+    // clicks this hand has produced over its lifetime
+    pub emitted: u64,
+    // filling, with a finger ready for longer than IDLE_FINGER_SECS
+    pub idle: bool,
+}
+
+// what a hand earns at the current multiplier and synergy, refreshed every
+// frame by compute_income_system
+#[derive(Component, Default)]
+pub struct HandIncome {
+    pub synergy: f64,
+    pub per_clap: u64,
+    pub per_second: u64,
+}
+
+#[derive(Bundle, Default)]
+pub struct Hand {
+    label: HandLabel,
+    state: HandState,
+    clap_timer: TillCanClickTimer,
+    name: HandName,
+    auto_buy: AutoBuy,
+    stats: HandStats,
+    income: HandIncome,
+}
+
+#[derive(Component, Default)]
+pub struct ClickerLabel;
+
+#[derive(Component)]
+pub struct TillCanClickTimer(pub Timer);
+
+impl Default for TillCanClickTimer {
+    fn default() -> Self {
+        TillCanClickTimer(Timer::from_seconds(1.0, TimerMode::Once))
+    }
+}
+
+// how long a finger has sat ready without being clicked
+#[derive(Component, Default)]
+pub struct ReadyTime(pub Duration);
+
+#[derive(Component)]
+pub struct ClickerState {
+    pub per_click: u64,
+}
+
+impl Default for ClickerState {
+    fn default() -> Self {
+        ClickerState { per_click: 1 }
+    }
+}
+
+#[derive(Bundle, Default)]
+pub struct Clicker {
+    label: ClickerLabel,
+    till_can_click: TillCanClickTimer,
+    ready_time: ReadyTime,
+    state: ClickerState,
+}
+
+impl Clicker {
+    pub fn with_per_click(per_click: u64) -> Self {
+        Clicker { state: ClickerState { per_click }, ..Clicker::default() }
+    }
+}
+
+pub type ClickerQuery<'w, 's> = Query<'w, 's, (&'static mut ClickerState, &'static mut TillCanClickTimer), With<ClickerLabel>>;
+
+pub type HandQuery<'w, 's> = Query<'w, 's, (
+    &'static mut HandState,
+    &'static mut TillCanClickTimer,
+    &'static Children,
+    Entity,
+    &'static mut HandName,
+    &'static mut AutoBuy,
+    &'static HandStats,
+    &'static HandIncome,
+), Without<ClickerState>>;
+
+// clicks a single clap of a hand is worth
+pub fn clap_clicks(clickers: &Children, all_clickers: &ClickerQuery, multiplier: u64) -> u64 {
+    let per_click: u64 = clickers.iter()
+        .filter_map(|clicker| all_clickers.get(*clicker).ok())
+        .map(|(state, _)| state.per_click)
+        .sum();
+    per_click.saturating_mul(multiplier)
+}
+
+pub fn spawn_hand(commands: &mut Commands, name: String, state: HandState, fingers: u32, per_click: u64) -> Entity {
+    // spawn with empty children so our query can find it
+    commands.spawn(Hand { state, name: HandName(name), ..Hand::default() }).with_children(|parent| {
+        for _ in 0..fingers {
+            parent.spawn(Clicker::with_per_click(per_click));
+        }
+    }).id()
+}
@@ -0,0 +1,80 @@
+use bevy::{
+    diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+};
+use bevy_egui::{egui, EguiContexts};
+use bevy_particle_systems::Playing;
+
+use crate::{
+    components::{default_hand_name, spawn_hand, ClickerLabel, HandLabel, HandState},
+    locale::Locale,
+    particles::PooledEmitter,
+    resources::{GameConfig, GameRng, Score},
+    states::State,
+};
+
+// cheats for balance testing. only built with `--features debug`.
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, debug_window.run_if(in_state(State::Game)));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn debug_window(
+    mut contexts: EguiContexts,
+    mut commands: Commands,
+    mut score: ResMut<Score>,
+    mut next_state: ResMut<NextState<State>>,
+    diagnostics: Res<DiagnosticsStore>,
+    rng: Res<GameRng>,
+    config: Res<GameConfig>,
+    locale: Res<Locale>,
+    hands: Query<(), With<HandLabel>>,
+    clickers: Query<(), With<ClickerLabel>>,
+    emitters: Query<(), (With<PooledEmitter>, With<Playing>)>,
+    mut clicks_input: Local<u64>,
+) {
+    egui::Window::new("Debug").id("debug".into()).show(contexts.ctx_mut(), |ui| {
+        let fps = diagnostics
+            .get(FrameTimeDiagnosticsPlugin::FPS)
+            .and_then(|fps| fps.smoothed());
+        match fps {
+            Some(fps) => ui.label(format!("FPS: {:.0}", fps)),
+            None => ui.label("FPS: —"),
+        };
+        ui.label(format!("Hands: {}", hands.iter().count()));
+        ui.label(format!("Clickers: {}", clickers.iter().count()));
+        ui.label(format!("Active particle systems: {}", emitters.iter().count()));
+        ui.label(format!("Seed: {}", rng.seed));
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.add(egui::DragValue::new(&mut *clicks_input).speed(1000.0));
+            if ui.button("Set clicks").clicked() {
+                score.stored_clicks = *clicks_input;
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Max clicks").clicked() {
+                score.stored_clicks = u64::MAX;
+            }
+            if ui.button("Add building").clicked() {
+                score.buildings += 1;
+            }
+            if ui.button("Spawn hand").clicked() {
+                // a hand with no fingers never clicks, start it like a new game's first
+                let count = hands.iter().count();
+                let fingers = config.first_hand_fingers();
+                spawn_hand(&mut commands, default_hand_name(&locale, count), HandState::Filling, fingers, score.per_click());
+                score.hands_owned += 1;
+                score.total_fingers += fingers as u64;
+            }
+        });
+        if ui.button("Jump to Finished").clicked() {
+            next_state.set(State::Finished);
+        }
+    });
+}
@@ -0,0 +1,1185 @@
+use serde::{Deserialize, Serialize};
+
+mod plugin;
+#[cfg(test)]
+mod sim;
+
+pub use plugin::{
+    apply_purchase_system, despawn_golden_click, golden_click_delay, golden_click_reward, Achievements, ClickReadyFingers, CpsHistory, EconomyPlugin,
+    GoldenClick, GoldenClickSpawner, MultiplierRaised, Purchase, PurchaseApplied, CPS_HISTORY_LEN,
+};
+#[cfg(test)]
+pub use plugin::{collect_score_system, play_time_system};
+
+pub const MULTIPLIER_TABLE : [u64; 19] = [
+    40, 80, 100, 150, 200, 250, 300, 350, 375, 400, 425, 450, 470, 475, 500, 525, 550, 575, 600
+];
+
+pub const CASHOUT_TABLE : [u64; 3] = [
+    10000, 100000000, 50000000000
+];
+// each cashout past the end of the table costs this much more than the one
+// before it, keeping pace with the prestige bonus
+pub const CASHOUT_GROWTH : u64 = 10;
+
+pub const WIN_SCORE : u64 = 1_000_000_000_000;
+
+pub const FINGER_BASE_COST : u64 = 10;
+pub const FINGER_COST_GROWTH : f64 = 1.04;
+pub const FINGER_REFUND_PERCENT : u64 = 50;
+
+pub const FINGER_UPGRADE_BASE_COST : u64 = 500;
+pub const FINGER_UPGRADE_COST_GROWTH : u64 = 4;
+
+pub const HAND_BASE_COST : u64 = 10;
+pub const HAND_COST_STEP : u64 = 10;
+// every hand gets its own window, so buying max stops here
+pub const MAX_HANDS_PER_PURCHASE : u64 = 100;
+pub const COMBINE_COST : u64 = 30;
+pub const AUTO_COST : u64 = 60;
+
+// multiplier gained per building past the first
+pub const PRESTIGE_BONUS : u64 = 10;
+
+// seconds between claps at each Faster Clapping tier
+pub const CLAP_PERIODS : [f32; 3] = [1.0, 0.5, 0.25];
+// cost of moving up from each tier
+pub const CLAP_SPEED_COSTS : [u64; 2] = [5000, 250000];
+
+// clicks spent on a cashout for each point of influence it earns
+pub const CLICKS_PER_INFLUENCE : u64 = 1000;
+pub const INFLUENCE_UPGRADE_BASE_COST : u64 = 10;
+pub const INFLUENCE_UPGRADE_COST_GROWTH : u64 = 3;
+
+// what each level of a meta upgrade is worth, in percent
+pub const META_INCOME_PERCENT : u64 = 10;
+pub const META_FINGER_DISCOUNT_PERCENT : u64 = 5;
+pub const META_MAX_FINGER_DISCOUNT_PERCENT : u64 = 50;
+// each level of these shaves the percent off what's left, so they never reach 0
+pub const META_CLAP_PERIOD_CUT_PERCENT : f32 = 10.0;
+pub const META_THRESHOLD_CUT_PERCENT : f64 = 5.0;
+// clicks kept through a cashout at the first Deep Pockets level, x10 per level after
+pub const META_DEEP_POCKETS_BASE_CLICKS : u64 = 1000;
+
+// boost from the first autoed hand to every other hand, in percent, and how
+// much of that each further autoed hand keeps. tops out at 5 / (1 - 0.8) = 25%.
+pub const SYNERGY_PERCENT : f64 = 5.0;
+pub const SYNERGY_DECAY : f64 = 0.8;
+
+// clicks per second every building earns without any hands
+pub const TRICKLE_PER_BUILDING : f64 = 0.5;
+
+// net worth counts this many seconds of current income on top of the bank
+pub const NET_WORTH_HORIZON_SECS : u64 = 3600;
+
+// every tunable number in the economy. the consts above are the defaults,
+// balance.ron can override any of them.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Balance {
+    pub multiplier_table: Vec<u64>,
+    pub cashout_table: Vec<u64>,
+    pub cashout_growth: u64,
+    pub win_score: u64,
+    pub finger_base_cost: u64,
+    pub finger_cost_growth: f64,
+    pub finger_refund_percent: u64,
+    pub finger_upgrade_base_cost: u64,
+    pub finger_upgrade_cost_growth: u64,
+    pub hand_base_cost: u64,
+    pub hand_cost_step: u64,
+    pub combine_cost: u64,
+    pub auto_cost: u64,
+    pub prestige_bonus: u64,
+    pub clap_periods: Vec<f32>,
+    pub clap_speed_costs: Vec<u64>,
+    pub clicks_per_influence: u64,
+    pub influence_upgrade_base_cost: u64,
+    pub influence_upgrade_cost_growth: u64,
+    pub synergy_percent: f64,
+    pub synergy_decay: f64,
+    pub trickle_per_building: f64,
+}
+
+impl Default for Balance {
+    fn default() -> Self {
+        Balance {
+            multiplier_table: MULTIPLIER_TABLE.to_vec(),
+            cashout_table: CASHOUT_TABLE.to_vec(),
+            cashout_growth: CASHOUT_GROWTH,
+            win_score: WIN_SCORE,
+            finger_base_cost: FINGER_BASE_COST,
+            finger_cost_growth: FINGER_COST_GROWTH,
+            finger_refund_percent: FINGER_REFUND_PERCENT,
+            finger_upgrade_base_cost: FINGER_UPGRADE_BASE_COST,
+            finger_upgrade_cost_growth: FINGER_UPGRADE_COST_GROWTH,
+            hand_base_cost: HAND_BASE_COST,
+            hand_cost_step: HAND_COST_STEP,
+            combine_cost: COMBINE_COST,
+            auto_cost: AUTO_COST,
+            prestige_bonus: PRESTIGE_BONUS,
+            clap_periods: CLAP_PERIODS.to_vec(),
+            clap_speed_costs: CLAP_SPEED_COSTS.to_vec(),
+            clicks_per_influence: CLICKS_PER_INFLUENCE,
+            influence_upgrade_base_cost: INFLUENCE_UPGRADE_BASE_COST,
+            influence_upgrade_cost_growth: INFLUENCE_UPGRADE_COST_GROWTH,
+            synergy_percent: SYNERGY_PERCENT,
+            synergy_decay: SYNERGY_DECAY,
+            trickle_per_building: TRICKLE_PER_BUILDING,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum BalanceError {
+    MultiplierTableNotIncreasing,
+    CashoutTableNotIncreasing,
+    CashoutGrowthTooSmall,
+    FingerCostShrinks,
+    RefundOverFullPrice,
+    ClapPeriodsNotDecreasing,
+    // there's one cost per step between clap periods
+    ClapSpeedCostCount { expected: usize, actual: usize },
+    ZeroWinScore,
+    ZeroClicksPerInfluence,
+    // a negative boost, or a decay that doesn't shrink
+    SynergyOutOfRange,
+    NegativeTrickle,
+}
+
+fn strictly_increasing<T: PartialOrd>(values: &[T]) -> bool {
+    values.windows(2).all(|pair| pair[0] < pair[1])
+}
+
+impl Balance {
+    pub fn validate(&self) -> Result<(), BalanceError> {
+        if !strictly_increasing(&self.multiplier_table) {
+            return Err(BalanceError::MultiplierTableNotIncreasing);
+        }
+        if !strictly_increasing(&self.cashout_table) {
+            return Err(BalanceError::CashoutTableNotIncreasing);
+        }
+        if self.cashout_growth < 2 {
+            return Err(BalanceError::CashoutGrowthTooSmall);
+        }
+        if self.finger_cost_growth.is_nan() || self.finger_cost_growth < 1.0 {
+            return Err(BalanceError::FingerCostShrinks);
+        }
+        if self.finger_refund_percent > 100 {
+            return Err(BalanceError::RefundOverFullPrice);
+        }
+        let periods_valid = !self.clap_periods.is_empty()
+            && self.clap_periods.iter().all(|period| *period > 0.0)
+            && self.clap_periods.windows(2).all(|pair| pair[0] > pair[1]);
+        if !periods_valid {
+            return Err(BalanceError::ClapPeriodsNotDecreasing);
+        }
+        let expected = self.clap_periods.len() - 1;
+        if self.clap_speed_costs.len() != expected {
+            return Err(BalanceError::ClapSpeedCostCount { expected, actual: self.clap_speed_costs.len() });
+        }
+        if self.win_score == 0 {
+            return Err(BalanceError::ZeroWinScore);
+        }
+        if self.clicks_per_influence == 0 {
+            return Err(BalanceError::ZeroClicksPerInfluence);
+        }
+        if !(self.synergy_percent >= 0.0 && (0.0..1.0).contains(&self.synergy_decay)) {
+            return Err(BalanceError::SynergyOutOfRange);
+        }
+        if self.trickle_per_building.is_nan() || self.trickle_per_building < 0.0 {
+            return Err(BalanceError::NegativeTrickle);
+        }
+        Ok(())
+    }
+
+    // percent `autoed` hands add to each hand other than themselves. every
+    // hand adds less than the one before, so the total levels off.
+    pub fn synergy(&self, autoed: u32) -> f64 {
+        let autoed = autoed.min(i32::MAX as u32) as i32;
+        self.synergy_percent * (1.0 - self.synergy_decay.powi(autoed)) / (1.0 - self.synergy_decay)
+    }
+}
+
+// clicks raised by a synergy percent, rounded down to the basis point
+pub fn with_synergy(clicks: u64, percent: f64) -> u64 {
+    let basis_points = (percent * 100.0).round().max(0.0) as u128;
+    u64::try_from(clicks as u128 * (10_000 + basis_points) / 10_000).unwrap_or(u64::MAX)
+}
+
+// the permanent upgrades influence buys, each with any number of levels
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MetaUpgrade {
+    // more income from every click
+    Income,
+    CheaperFingers,
+    // shorter clap timers
+    NimbleFingers,
+    // lower multiplier table thresholds
+    ViralGrowth,
+    // clicks kept through a cashout
+    DeepPockets,
+}
+
+impl MetaUpgrade {
+    pub const ALL : [MetaUpgrade; 5] = [
+        MetaUpgrade::Income,
+        MetaUpgrade::CheaperFingers,
+        MetaUpgrade::NimbleFingers,
+        MetaUpgrade::ViralGrowth,
+        MetaUpgrade::DeepPockets,
+    ];
+
+    // for the `influence.<key>.*` strings
+    pub fn key(self) -> &'static str {
+        match self {
+            MetaUpgrade::Income => "income",
+            MetaUpgrade::CheaperFingers => "cheaper_fingers",
+            MetaUpgrade::NimbleFingers => "nimble_fingers",
+            MetaUpgrade::ViralGrowth => "viral_growth",
+            MetaUpgrade::DeepPockets => "deep_pockets",
+        }
+    }
+}
+
+// levels bought in each meta upgrade. the economy applies these on top of
+// whatever balance is in play, so balance.ron edits keep working.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetaUpgrades {
+    pub income: u32,
+    pub cheaper_fingers: u32,
+    pub nimble_fingers: u32,
+    pub viral_growth: u32,
+    pub deep_pockets: u32,
+}
+
+impl MetaUpgrades {
+    pub fn level(&self, upgrade: MetaUpgrade) -> u32 {
+        match upgrade {
+            MetaUpgrade::Income => self.income,
+            MetaUpgrade::CheaperFingers => self.cheaper_fingers,
+            MetaUpgrade::NimbleFingers => self.nimble_fingers,
+            MetaUpgrade::ViralGrowth => self.viral_growth,
+            MetaUpgrade::DeepPockets => self.deep_pockets,
+        }
+    }
+
+    fn level_mut(&mut self, upgrade: MetaUpgrade) -> &mut u32 {
+        match upgrade {
+            MetaUpgrade::Income => &mut self.income,
+            MetaUpgrade::CheaperFingers => &mut self.cheaper_fingers,
+            MetaUpgrade::NimbleFingers => &mut self.nimble_fingers,
+            MetaUpgrade::ViralGrowth => &mut self.viral_growth,
+            MetaUpgrade::DeepPockets => &mut self.deep_pockets,
+        }
+    }
+
+    pub fn income_percent(&self) -> u64 {
+        100 + META_INCOME_PERCENT * self.income as u64
+    }
+
+    pub fn finger_discount_percent(&self) -> u64 {
+        (META_FINGER_DISCOUNT_PERCENT * self.cheaper_fingers as u64).min(META_MAX_FINGER_DISCOUNT_PERCENT)
+    }
+
+    pub fn clap_period_scale(&self) -> f32 {
+        (1.0 - META_CLAP_PERIOD_CUT_PERCENT / 100.0).powi(self.nimble_fingers as i32)
+    }
+
+    // never below one finger
+    pub fn threshold(&self, fingers: u64) -> u64 {
+        let scale = (1.0 - META_THRESHOLD_CUT_PERCENT / 100.0).powi(self.viral_growth as i32);
+        ((fingers as f64 * scale).ceil() as u64).max(1)
+    }
+
+    pub fn starting_clicks(&self) -> u64 {
+        match self.deep_pockets {
+            0 => 0,
+            level => META_DEEP_POCKETS_BASE_CLICKS.saturating_mul(10u64.saturating_pow(level - 1)),
+        }
+    }
+}
+
+// picked when a game starts and fixed until the next one. scales prices,
+// multiplier thresholds and the win score of whatever balance is in play.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL : [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn label_key(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "difficulty.easy",
+            Difficulty::Normal => "difficulty.normal",
+            Difficulty::Hard => "difficulty.hard",
+        }
+    }
+
+    // of every price in clicks
+    pub fn cost_percent(self) -> u64 {
+        match self {
+            Difficulty::Easy => 75,
+            Difficulty::Normal => 100,
+            Difficulty::Hard => 150,
+        }
+    }
+
+    // of the finger counts in the multiplier table
+    pub fn threshold_percent(self) -> u64 {
+        match self {
+            Difficulty::Easy => 80,
+            Difficulty::Normal => 100,
+            Difficulty::Hard => 125,
+        }
+    }
+
+    pub fn win_percent(self) -> u64 {
+        match self {
+            Difficulty::Easy => 50,
+            Difficulty::Normal => 100,
+            Difficulty::Hard => 200,
+        }
+    }
+}
+
+pub fn percent_of(value: u64, percent: u64) -> u64 {
+    u64::try_from(value as u128 * percent as u128 / 100).unwrap_or(u64::MAX)
+}
+
+// the sum of (step * i + start) / divisor, rounded down term by term, for i
+// in 0..count. each round folds the whole parts out and swaps the roles of
+// step and divisor, like Euclid's algorithm, so it takes O(log) rounds.
+fn floor_sum(mut count: u128, mut divisor: u128, mut step: u128, mut start: u128) -> u128 {
+    let mut sum = 0u128;
+    loop {
+        if step >= divisor {
+            sum += count * count.saturating_sub(1) / 2 * (step / divisor);
+            step %= divisor;
+        }
+        if start >= divisor {
+            sum += count * (start / divisor);
+            start %= divisor;
+        }
+        let last = step * count + start;
+        if last < divisor {
+            return sum;
+        }
+        count = last / divisor;
+        start = last % divisor;
+        std::mem::swap(&mut divisor, &mut step);
+    }
+}
+
+// how much a click per second is worth to the smart buy key, by how much
+// work it takes. manual fingers need a click each, combined hands one clap
+// for the lot, autoed hands nothing at all.
+pub const MANUAL_VALUE : f64 = 0.5;
+pub const COMBINED_VALUE : f64 = 0.75;
+pub const AUTOED_VALUE : f64 = 1.0;
+
+// the affordable option that buys a click per second for the fewest clicks.
+// options are (what, cost, clicks per second gained).
+pub fn best_value<T: Copy>(options: &[(T, u64, f64)], budget: u64) -> Option<T> {
+    options
+        .iter()
+        .filter(|(_, cost, gain)| *cost <= budget && *gain > 0.0)
+        .min_by(|(_, a_cost, a_gain), (_, b_cost, b_gain)| (*a_cost as f64 / a_gain).total_cmp(&(*b_cost as f64 / b_gain)))
+        .map(|(option, ..)| *option)
+}
+
+// buying fingers up to the next multiplier tier against cashing out, both
+// aimed at the same goal in the bank. income is taken to scale with the
+// multiplier, which ignores the hands a cashout gives up, but treats both
+// paths alike.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrestigeAdvice {
+    // the fingers still missing for the next tier, their cost, and the
+    // multiplier once they're bought
+    pub tier_fingers: u64,
+    pub tier_cost: u64,
+    pub tier_multiplier: u64,
+    pub cashout_cost: u64,
+    pub cashout_multiplier: u64,
+    // seconds to the goal down either path
+    pub grind_secs: f64,
+    pub cashout_secs: f64,
+}
+
+impl PrestigeAdvice {
+    pub fn cash_out(&self) -> bool {
+        self.cashout_secs < self.grind_secs
+    }
+}
+
+// seconds to save up `cost` out of `stored` and then reach `goal` at
+// `rate` times the income, starting over from `left` clicks
+fn secs_via(stored: u64, cost: u64, left: u64, goal: u64, clicks_per_second: f64, rate: f64) -> f64 {
+    let saving = cost.saturating_sub(stored) as f64 / clicks_per_second;
+    saving + goal.saturating_sub(left) as f64 / (clicks_per_second * rate)
+}
+
+// plain progression data, kept free of bevy so balance can be tested directly
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Economy {
+    pub stored_clicks: u64,
+    pub total_fingers: u64,
+    // hands bought. the free first hand isn't counted, and hand_cost goes up
+    // with each. saves from before the split double counted combined hands.
+    #[serde(alias = "total_hands")]
+    pub hands_owned: u64,
+    pub hands_combined: u64,
+    pub buildings: u32,
+    // each level adds one click to every finger's per_click
+    pub finger_upgrades: u32,
+    // index into CLAP_PERIODS
+    pub clap_speed: u32,
+    // earned by cashing out. it and what it bought are kept through cashouts.
+    pub influence: u64,
+    pub meta_upgrades: MetaUpgrades,
+    // keep playing past the win score
+    pub sandbox: bool,
+    pub difficulty: Difficulty,
+}
+
+impl Default for Economy {
+    fn default() -> Self {
+        Economy {
+            stored_clicks: 0,
+            total_fingers: 1,
+            hands_owned: 0,
+            hands_combined: 0,
+            buildings: 1,
+            finger_upgrades: 0,
+            clap_speed: 0,
+            influence: 0,
+            meta_upgrades: MetaUpgrades::default(),
+            sandbox: false,
+            difficulty: Difficulty::default(),
+        }
+    }
+}
+
+impl Economy {
+    // every price in clicks goes through here, so difficulty applies to all of them
+    fn price(&self, clicks: u64) -> u64 {
+        percent_of(clicks, self.difficulty.cost_percent())
+    }
+
+    // fingers needed for a multiplier table entry
+    fn threshold(&self, fingers: u64) -> u64 {
+        self.meta_upgrades.threshold(percent_of(fingers, self.difficulty.threshold_percent()))
+    }
+
+    pub fn win_score(&self, balance: &Balance) -> u64 {
+        percent_of(balance.win_score, self.difficulty.win_percent()).max(1)
+    }
+
+    pub fn finger_cost(&self, balance: &Balance) -> u64 {
+        let growth = balance.finger_cost_growth.powf(self.total_fingers as f64) as u64;
+        let cost = self.price(balance.finger_base_cost.saturating_mul(growth));
+        let discounted = cost as u128 * (100 - self.meta_upgrades.finger_discount_percent()) as u128 / 100;
+        discounted as u64
+    }
+
+    // how many fingers `budget` buys in a row, and what they cost in total
+    pub fn affordable_fingers(&self, balance: &Balance, budget: u64) -> (u64, u64) {
+        let mut simulated = self.clone();
+        let mut count = 0;
+        let mut total_cost = 0u64;
+        loop {
+            let cost = simulated.finger_cost(balance);
+            match total_cost.checked_add(cost) {
+                Some(next) if next <= budget => total_cost = next,
+                _ => break,
+            }
+            simulated.total_fingers += 1;
+            count += 1;
+        }
+        (count, total_cost)
+    }
+
+    // half of what the most recent finger cost
+    pub fn finger_refund(&self, balance: &Balance) -> u64 {
+        let before = Economy { total_fingers: self.total_fingers.saturating_sub(1), ..self.clone() };
+        before.finger_cost(balance).saturating_mul(balance.finger_refund_percent) / 100
+    }
+
+    // a hand always keeps at least one finger, so this refuses when
+    // `fingers_on_hand` <= 1
+    pub fn sell_finger(&mut self, balance: &Balance, fingers_on_hand: u64) -> Option<u64> {
+        if fingers_on_hand <= 1 || self.total_fingers <= 1 {
+            return None;
+        }
+        let refund = self.finger_refund(balance);
+        self.total_fingers -= 1;
+        self.stored_clicks = self.stored_clicks.saturating_add(refund);
+        Some(refund)
+    }
+
+    pub fn per_click(&self) -> u64 {
+        1 + self.finger_upgrades as u64
+    }
+
+    pub fn finger_upgrade_cost(&self, balance: &Balance) -> u64 {
+        self.price(balance.finger_upgrade_cost_growth
+            .saturating_pow(self.finger_upgrades)
+            .saturating_mul(balance.finger_upgrade_base_cost))
+    }
+
+    pub fn clap_period(&self, balance: &Balance) -> f32 {
+        // a validated balance always has at least one period
+        let fastest = balance.clap_periods.len().saturating_sub(1);
+        let period = balance.clap_periods.get((self.clap_speed as usize).min(fastest)).copied().unwrap_or(1.0);
+        period * self.meta_upgrades.clap_period_scale()
+    }
+
+    pub fn claps_per_second(&self, balance: &Balance) -> f32 {
+        1.0 / self.clap_period(balance)
+    }
+
+    // passive clicks per second, before the influence bonus. every cashout
+    // adds a building, so it grows a little with each one.
+    pub fn trickle_rate(&self, balance: &Balance) -> f64 {
+        self.buildings as f64 * balance.trickle_per_building
+    }
+
+    // None once clapping is as fast as it gets
+    pub fn clap_speed_cost(&self, balance: &Balance) -> Option<u64> {
+        balance.clap_speed_costs.get(self.clap_speed as usize).map(|cost| self.price(*cost))
+    }
+
+    pub fn hand_cost(&self, balance: &Balance) -> u64 {
+        self.price(self.hands_owned.saturating_mul(balance.hand_cost_step).saturating_add(balance.hand_base_cost))
+    }
+
+    // what the next `count` hands cost together. the prices rise in a
+    // straight line, each rounded down like hand_cost rounds it.
+    fn hands_cost(&self, balance: &Balance, count: u64) -> u128 {
+        let percent = self.difficulty.cost_percent() as u128;
+        let first = self.hands_owned as u128 * balance.hand_cost_step as u128 + balance.hand_base_cost as u128;
+        floor_sum(count as u128, 100, balance.hand_cost_step as u128 * percent, first * percent)
+    }
+
+    // how many hands `budget` buys in a row, up to MAX_HANDS_PER_PURCHASE,
+    // and what they cost together. each one raises the price of the next.
+    pub fn affordable_hands(&self, balance: &Balance, budget: u64) -> (u64, u64) {
+        // the total only grows with the count, so halve the range until
+        // the largest affordable count is left
+        let (mut affordable, mut unaffordable) = (0, MAX_HANDS_PER_PURCHASE + 1);
+        while unaffordable - affordable > 1 {
+            let count = affordable + (unaffordable - affordable) / 2;
+            if self.hands_cost(balance, count) <= budget as u128 {
+                affordable = count;
+            } else {
+                unaffordable = count;
+            }
+        }
+        (affordable, self.hands_cost(balance, affordable) as u64)
+    }
+
+    pub fn combine_cost(&self, balance: &Balance) -> u64 {
+        self.price(balance.combine_cost)
+    }
+
+    pub fn auto_cost(&self, balance: &Balance) -> u64 {
+        self.price(balance.auto_cost)
+    }
+
+    pub fn multiplier(&self, balance: &Balance) -> u64 {
+        let mut multiplier = 1u64;
+        for lmt in balance.multiplier_table.iter() {
+            if self.total_fingers >= self.threshold(*lmt) {
+                multiplier = multiplier.saturating_mul(2);
+            }
+        };
+        // prestige bonus
+        multiplier.saturating_mul(balance.prestige_bonus.saturating_pow(self.buildings.saturating_sub(1)))
+    }
+
+    // the multiplier once `fingers` more have been bought
+    pub fn multiplier_with_fingers(&self, balance: &Balance, fingers: u64) -> u64 {
+        Economy { total_fingers: self.total_fingers.saturating_add(fingers), ..self.clone() }.multiplier(balance)
+    }
+
+    pub fn next_multiplier(&self, balance: &Balance) -> Option<u64> {
+        for lmt in balance.multiplier_table.iter() {
+            let lmt = self.threshold(*lmt);
+            if self.total_fingers < lmt {
+                return Some(lmt);
+            }
+        };
+        None
+    }
+
+    // clicks as they land in the bank, after the influence income bonus
+    pub fn boosted(&self, clicks: u64) -> u64 {
+        let boosted = clicks as u128 * self.meta_upgrades.income_percent() as u128 / 100;
+        u64::try_from(boosted).unwrap_or(u64::MAX)
+    }
+
+    pub fn meta_upgrade_cost(&self, balance: &Balance, upgrade: MetaUpgrade) -> u64 {
+        let growth = balance.influence_upgrade_cost_growth.saturating_pow(self.meta_upgrades.level(upgrade));
+        balance.influence_upgrade_base_cost.saturating_mul(growth)
+    }
+
+    // false, changing nothing, when there isn't enough influence
+    pub fn buy_meta_upgrade(&mut self, balance: &Balance, upgrade: MetaUpgrade) -> bool {
+        let cost = self.meta_upgrade_cost(balance, upgrade);
+        if self.influence < cost {
+            return false;
+        }
+        self.influence -= cost;
+        *self.meta_upgrades.level_mut(upgrade) += 1;
+        true
+    }
+
+    // what cashing out now would earn. it goes by the undiscounted price,
+    // so influence doesn't depend on difficulty.
+    pub fn cashout_influence(&self, balance: &Balance) -> Option<u64> {
+        Some(self.base_cashout_cost(balance)? / balance.clicks_per_influence.max(1))
+    }
+
+    // clicks per second from a hand worth `per_clap` each time it goes off.
+    // fingers click once a second, combined and autoed hands clap as fast
+    // as they've been upgraded to.
+    pub fn hand_rate(&self, balance: &Balance, per_clap: u64, clapping: bool) -> u64 {
+        if clapping {
+            (per_clap as f64 * self.claps_per_second(balance) as f64).round() as u64
+        } else {
+            per_clap
+        }
+    }
+
+    // stored clicks plus NET_WORTH_HORIZON_SECS of `clicks_per_second`, the
+    // hands' income without the trickle, after the influence bonus. a
+    // cashout empties the bank and all but one hand, so it drops then too.
+    pub fn net_worth(&self, clicks_per_second: u64) -> u64 {
+        let future = self.boosted(clicks_per_second).saturating_mul(NET_WORTH_HORIZON_SECS);
+        self.stored_clicks.saturating_add(future)
+    }
+
+    // clicks per second a single finger makes going off every clap period
+    pub fn finger_rate(&self, balance: &Balance) -> f64 {
+        self.per_click() as f64 * self.multiplier(balance) as f64 * self.claps_per_second(balance) as f64
+    }
+
+    // clicking the building at `index` pays the multiplier once per tier, the
+    // first building being tier 1
+    pub fn building_click(&self, balance: &Balance, index: usize) -> u64 {
+        self.multiplier(balance).saturating_mul(index as u64 + 1)
+    }
+
+    pub fn cashout_cost(&self, balance: &Balance) -> Option<u64> {
+        self.base_cashout_cost(balance).map(|cost| self.price(cost))
+    }
+
+    pub fn can_cash_out(&self, balance: &Balance) -> bool {
+        self.cashout_cost(balance).is_some_and(|cost| self.stored_clicks >= cost)
+    }
+
+    fn base_cashout_cost(&self, balance: &Balance) -> Option<u64> {
+        // buildings starts at 1, but never trust it not to be 0
+        if self.buildings == 0 {
+            return None;
+        }
+        let index = self.buildings as usize - 1;
+        if let Some(cost) = balance.cashout_table.get(index) {
+            return Some(*cost);
+        }
+        // past the table costs grow geometrically from its last entry. once
+        // that would overflow there are no more cashouts.
+        let steps = u32::try_from(index + 1 - balance.cashout_table.len()).ok()?;
+        balance.cashout_table.last()?.checked_mul(balance.cashout_growth.checked_pow(steps)?)
+    }
+
+    // one more building and some influence, with everything else bought this
+    // run given up. None when there's no cashout left; affordability is up to
+    // the caller.
+    pub fn cashed_out(&self, balance: &Balance) -> Option<Economy> {
+        let cost = self.cashout_cost(balance)?;
+        let earned = self.cashout_influence(balance)?;
+        Some(Economy {
+            stored_clicks: self.stored_clicks.saturating_sub(cost).max(self.meta_upgrades.starting_clicks()),
+            buildings: self.buildings + 1,
+            influence: self.influence.saturating_add(earned),
+            meta_upgrades: self.meta_upgrades,
+            sandbox: self.sandbox,
+            difficulty: self.difficulty,
+            ..Economy::default()
+        })
+    }
+
+    // the fingers missing for the next multiplier tier and their total cost
+    pub fn next_tier_fingers(&self, balance: &Balance) -> Option<(u64, u64)> {
+        let fingers = self.next_multiplier(balance)?.saturating_sub(self.total_fingers);
+        let mut simulated = self.clone();
+        let mut cost = 0u64;
+        for _ in 0..fingers {
+            cost = cost.saturating_add(simulated.finger_cost(balance));
+            simulated.total_fingers += 1;
+        }
+        Some((fingers, cost))
+    }
+
+    // None without income, or when there's no tier or no cashout left to compare
+    pub fn prestige_advice(&self, balance: &Balance, clicks_per_second: f64, goal: u64) -> Option<PrestigeAdvice> {
+        if clicks_per_second.is_nan() || clicks_per_second <= 0.0 {
+            return None;
+        }
+        let multiplier = self.multiplier(balance).max(1) as f64;
+        let (tier_fingers, tier_cost) = self.next_tier_fingers(balance)?;
+        let tier_multiplier = self.multiplier_with_fingers(balance, tier_fingers);
+        let cashout_cost = self.cashout_cost(balance)?;
+        // what's left once the cashout has been saved up for
+        let cashed_out = Economy { stored_clicks: self.stored_clicks.max(cashout_cost), ..self.clone() }.cashed_out(balance)?;
+        let cashout_multiplier = cashed_out.multiplier(balance);
+        Some(PrestigeAdvice {
+            tier_fingers,
+            tier_cost,
+            tier_multiplier,
+            cashout_cost,
+            cashout_multiplier,
+            grind_secs: secs_via(
+                self.stored_clicks,
+                tier_cost,
+                self.stored_clicks.saturating_sub(tier_cost),
+                goal,
+                clicks_per_second,
+                tier_multiplier as f64 / multiplier,
+            ),
+            cashout_secs: secs_via(
+                self.stored_clicks,
+                cashout_cost,
+                cashed_out.stored_clicks,
+                goal,
+                clicks_per_second,
+                cashout_multiplier as f64 / multiplier,
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn balance() -> Balance {
+        Balance::default()
+    }
+
+    fn with_fingers(total_fingers: u64) -> Economy {
+        Economy { total_fingers, ..Economy::default() }
+    }
+
+    #[test]
+    fn finger_cost_grows_with_fingers() {
+        assert_eq!(with_fingers(1).finger_cost(&balance()), 10);
+        assert_eq!(with_fingers(18).finger_cost(&balance()), 20);
+        assert_eq!(with_fingers(100).finger_cost(&balance()), 500);
+        let costs: Vec<u64> = (1..600).map(|n| with_fingers(n).finger_cost(&balance())).collect();
+        assert!(costs.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn affordable_hands_pay_the_rising_price() {
+        let economy = Economy::default();
+        // 10, 20, 30, 40...
+        assert_eq!(economy.affordable_hands(&balance(), 0), (0, 0));
+        assert_eq!(economy.affordable_hands(&balance(), 9), (0, 0));
+        assert_eq!(economy.affordable_hands(&balance(), 10), (1, 10));
+        assert_eq!(economy.affordable_hands(&balance(), 59), (2, 30));
+        assert_eq!(economy.affordable_hands(&balance(), 60), (3, 60));
+        assert_eq!(economy.affordable_hands(&balance(), 5_500), (32, 5_280));
+        let later = Economy { hands_owned: 4, ..Economy::default() };
+        assert_eq!(later.affordable_hands(&balance(), 110), (2, 110));
+        let hard = Economy { difficulty: Difficulty::Hard, ..Economy::default() };
+        assert_eq!(hard.affordable_hands(&balance(), 45), (2, 45));
+        assert_eq!(Economy::default().affordable_hands(&balance(), u64::MAX).0, MAX_HANDS_PER_PURCHASE);
+    }
+
+    #[test]
+    fn affordable_hands_matches_buying_one_at_a_time() {
+        for difficulty in [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard] {
+            for (hands_owned, budget) in [(0, 44), (0, 45), (3, 1_000), (7, 12_345), (250, 1_000_000)] {
+                let economy = Economy { difficulty, hands_owned, ..Economy::default() };
+                let mut manual = economy.clone();
+                let mut spent = 0;
+                while manual.hands_owned - hands_owned < MAX_HANDS_PER_PURCHASE
+                    && spent + manual.hand_cost(&balance()) <= budget
+                {
+                    spent += manual.hand_cost(&balance());
+                    manual.hands_owned += 1;
+                }
+                let expected = (manual.hands_owned - hands_owned, spent);
+                assert_eq!(economy.affordable_hands(&balance(), budget), expected, "{difficulty:?} {hands_owned} {budget}");
+            }
+        }
+    }
+
+    #[test]
+    fn affordable_fingers_recomputes_cost_after_each_purchase() {
+        let economy = with_fingers(1);
+        assert_eq!(economy.affordable_fingers(&balance(), 0), (0, 0));
+        assert_eq!(economy.affordable_fingers(&balance(), 9), (0, 0));
+        assert_eq!(economy.affordable_fingers(&balance(), 10), (1, 10));
+        assert_eq!(economy.affordable_fingers(&balance(), 10 * 17 + 19), (17, 170));
+        assert_eq!(economy.affordable_fingers(&balance(), 10 * 17 + 20), (18, 190));
+
+        let (count, cost) = with_fingers(50).affordable_fingers(&balance(), 1_000_000);
+        let mut manual = with_fingers(50);
+        let mut spent = 0;
+        for _ in 0..count {
+            spent += manual.finger_cost(&balance());
+            manual.total_fingers += 1;
+        }
+        assert_eq!(spent, cost);
+        assert!(spent + manual.finger_cost(&balance()) > 1_000_000);
+    }
+
+    #[test]
+    fn selling_a_finger_refunds_half_and_drops_the_multiplier() {
+        let mut economy = with_fingers(40);
+        assert_eq!(economy.multiplier(&balance()), 2);
+        let refund = economy.sell_finger(&balance(), 5);
+        assert_eq!(refund, Some(with_fingers(39).finger_cost(&balance()) / 2));
+        assert_eq!(economy.stored_clicks, refund.unwrap());
+        assert_eq!(economy.total_fingers, 39);
+        assert_eq!(economy.multiplier(&balance()), 1);
+    }
+
+    #[test]
+    fn cannot_sell_a_hands_last_finger() {
+        let mut economy = with_fingers(10);
+        assert_eq!(economy.sell_finger(&balance(), 1), None);
+        assert_eq!(economy.sell_finger(&balance(), 0), None);
+        assert_eq!(economy, with_fingers(10));
+    }
+
+    #[test]
+    fn finger_upgrades_escalate() {
+        let mut economy = Economy::default();
+        assert_eq!(economy.per_click(), 1);
+        assert_eq!(economy.finger_upgrade_cost(&balance()), 500);
+        economy.finger_upgrades = 1;
+        assert_eq!(economy.per_click(), 2);
+        assert_eq!(economy.finger_upgrade_cost(&balance()), 2_000);
+        economy.finger_upgrades = 3;
+        assert_eq!(economy.finger_upgrade_cost(&balance()), 32_000);
+        economy.finger_upgrades = 100;
+        assert_eq!(economy.finger_upgrade_cost(&balance()), u64::MAX);
+    }
+
+    #[test]
+    fn faster_clapping_shortens_the_period() {
+        let mut economy = Economy::default();
+        assert_eq!(economy.claps_per_second(&balance()), 1.0);
+        assert_eq!(economy.clap_speed_cost(&balance()), Some(5000));
+        economy.clap_speed = 1;
+        assert_eq!(economy.claps_per_second(&balance()), 2.0);
+        economy.clap_speed = 2;
+        assert_eq!(economy.clap_period(&balance()), 0.25);
+        assert_eq!(economy.claps_per_second(&balance()), 4.0);
+        assert_eq!(economy.clap_speed_cost(&balance()), None);
+        // out of range tiers stay at the fastest period
+        economy.clap_speed = 9;
+        assert_eq!(economy.claps_per_second(&balance()), 4.0);
+    }
+
+    #[test]
+    fn hand_cost_grows_linearly() {
+        let mut economy = Economy::default();
+        assert_eq!(economy.hand_cost(&balance()), 10);
+        economy.hands_owned = 4;
+        assert_eq!(economy.hand_cost(&balance()), 50);
+    }
+
+    #[test]
+    fn flat_costs() {
+        let economy = Economy::default();
+        assert_eq!(economy.combine_cost(&balance()), 30);
+        assert_eq!(economy.auto_cost(&balance()), 60);
+    }
+
+    #[test]
+    fn multiplier_doubles_at_each_tier() {
+        assert_eq!(with_fingers(1).multiplier(&balance()), 1);
+        assert_eq!(with_fingers(39).multiplier(&balance()), 1);
+        assert_eq!(with_fingers(40).multiplier(&balance()), 2);
+        assert_eq!(with_fingers(80).multiplier(&balance()), 4);
+        for (tier, lmt) in MULTIPLIER_TABLE.iter().enumerate() {
+            assert_eq!(with_fingers(*lmt).multiplier(&balance()), 2u64.pow(tier as u32 + 1));
+        }
+    }
+
+    #[test]
+    fn multiplier_with_fingers_looks_ahead() {
+        assert_eq!(with_fingers(39).multiplier_with_fingers(&balance(), 0), 1);
+        assert_eq!(with_fingers(39).multiplier_with_fingers(&balance(), 1), 2);
+    }
+
+    #[test]
+    fn multiplier_includes_prestige_bonus() {
+        let economy = Economy { total_fingers: 40, buildings: 3, ..Economy::default() };
+        assert_eq!(economy.multiplier(&balance()), 200);
+    }
+
+    #[test]
+    fn later_buildings_pay_more_per_click() {
+        let economy = Economy { total_fingers: 40, buildings: 2, ..Economy::default() };
+        assert_eq!(economy.building_click(&balance(), 0), 20);
+        assert_eq!(economy.building_click(&balance(), 1), 40);
+    }
+
+    #[test]
+    fn best_value_picks_the_cheapest_rate() {
+        let options = [("finger", 10, 0.5), ("combine", 30, 3.0), ("auto", 60, 2.0), ("free", 0, 0.0)];
+        assert_eq!(best_value(&options, 100), Some("combine"));
+        assert_eq!(best_value(&options, 20), Some("finger"));
+        assert_eq!(best_value(&options, 5), None);
+    }
+
+    #[test]
+    fn next_multiplier_walks_the_table() {
+        assert_eq!(with_fingers(1).next_multiplier(&balance()), Some(40));
+        assert_eq!(with_fingers(40).next_multiplier(&balance()), Some(80));
+        assert_eq!(with_fingers(599).next_multiplier(&balance()), Some(600));
+        assert_eq!(with_fingers(600).next_multiplier(&balance()), None);
+    }
+
+    #[test]
+    fn advice_weighs_the_next_tier_against_a_cashout() {
+        let almost = with_fingers(39);
+        assert_eq!(almost.next_tier_fingers(&balance()), Some((1, almost.finger_cost(&balance()))));
+
+        // a fresh run has no tiers to lose, so the prestige bonus wins
+        let fresh = with_fingers(1).prestige_advice(&balance(), 1e6, WIN_SCORE).unwrap();
+        assert_eq!((fresh.tier_fingers, fresh.tier_multiplier), (39, 2));
+        assert_eq!((fresh.cashout_cost, fresh.cashout_multiplier), (10_000, PRESTIGE_BONUS));
+        assert!(fresh.cash_out());
+
+        // seven tiers in, a cashout would give most of them up
+        let deep = with_fingers(349).prestige_advice(&balance(), 1e6, WIN_SCORE).unwrap();
+        assert_eq!((deep.tier_fingers, deep.tier_multiplier), (1, 256));
+        assert!(!deep.cash_out());
+
+        assert_eq!(with_fingers(1).prestige_advice(&balance(), 0.0, WIN_SCORE), None);
+        assert_eq!(with_fingers(600).prestige_advice(&balance(), 1e6, WIN_SCORE), None);
+    }
+
+    #[test]
+    fn cashout_cost_follows_the_table() {
+        for (idx, cost) in CASHOUT_TABLE.iter().enumerate() {
+            let economy = Economy { buildings: idx as u32 + 1, ..Economy::default() };
+            assert_eq!(economy.cashout_cost(&balance()), Some(*cost));
+        }
+        // and then keep growing past its end
+        let economy = Economy { buildings: CASHOUT_TABLE.len() as u32 + 1, ..Economy::default() };
+        assert_eq!(economy.cashout_cost(&balance()), Some(500_000_000_000));
+    }
+
+    #[test]
+    fn cashout_cost_bounds() {
+        let with_buildings = |buildings| Economy { buildings, ..Economy::default() };
+        assert_eq!(with_buildings(0).cashout_cost(&balance()), None);
+        assert_eq!(with_buildings(1).cashout_cost(&balance()), Some(10000));
+        assert_eq!(with_buildings(3).cashout_cost(&balance()), Some(50000000000));
+        assert_eq!(with_buildings(4).cashout_cost(&balance()), Some(500_000_000_000));
+        assert_eq!(with_buildings(5).cashout_cost(&balance()), Some(5_000_000_000_000));
+        assert_eq!(with_buildings(10).cashout_cost(&balance()), Some(500_000_000_000_000_000));
+        // capped rather than overflowing
+        assert_eq!(with_buildings(12).cashout_cost(&balance()), None);
+        assert_eq!(with_buildings(u32::MAX).cashout_cost(&balance()), None);
+    }
+
+    #[test]
+    fn sandbox_survives_cashouts() {
+        let economy = Economy { stored_clicks: 10_000, sandbox: true, ..Economy::default() };
+        assert!(economy.cashed_out(&balance()).unwrap().sandbox);
+    }
+
+    #[test]
+    fn cashing_out_trades_fingers_for_a_building() {
+        let economy = Economy {
+            stored_clicks: 15_000,
+            total_fingers: 80,
+            hands_owned: 6,
+            finger_upgrades: 2,
+            clap_speed: 1,
+            ..Economy::default()
+        };
+        assert_eq!(economy.multiplier(&balance()), 4);
+        let after = economy.cashed_out(&balance()).unwrap();
+        assert_eq!(after, Economy { stored_clicks: 5_000, buildings: 2, influence: 10, ..Economy::default() });
+        assert_eq!(after.multiplier(&balance()), 10);
+
+        let maxed = Economy { buildings: 12, ..Economy::default() };
+        assert_eq!(maxed.cashed_out(&balance()), None);
+    }
+
+    #[test]
+    fn meta_upgrades_survive_cashouts() {
+        let mut economy = Economy { stored_clicks: 100_000_000, buildings: 2, influence: 45, ..Economy::default() };
+        assert!(economy.buy_meta_upgrade(&balance(), MetaUpgrade::Income));
+        assert_eq!(economy.meta_upgrade_cost(&balance(), MetaUpgrade::Income), 30);
+        assert!(economy.buy_meta_upgrade(&balance(), MetaUpgrade::Income));
+        assert!(!economy.buy_meta_upgrade(&balance(), MetaUpgrade::NimbleFingers));
+        assert_eq!(economy.influence, 5);
+
+        let after = economy.cashed_out(&balance()).unwrap();
+        assert_eq!(after.influence, 100_005);
+        assert_eq!(after.meta_upgrades.income, 2);
+        assert_eq!(after.boosted(1_000), 1_200);
+    }
+
+    #[test]
+    fn meta_upgrades_discount_fingers_and_speed_claps() {
+        let mut economy = with_fingers(18);
+        economy.meta_upgrades.cheaper_fingers = 2;
+        assert_eq!(economy.finger_cost(&balance()), 18);
+        economy.meta_upgrades.cheaper_fingers = 100;
+        assert_eq!(economy.finger_cost(&balance()), 10);
+        economy.meta_upgrades.nimble_fingers = 1;
+        assert_eq!(economy.clap_period(&balance()), 0.9);
+    }
+
+    #[test]
+    fn viral_growth_lowers_thresholds_and_deep_pockets_keeps_clicks() {
+        let mut economy = with_fingers(38);
+        assert_eq!(economy.multiplier(&balance()), 1);
+        economy.meta_upgrades.viral_growth = 1;
+        assert_eq!(economy.meta_upgrades.threshold(40), 38);
+        assert_eq!(economy.multiplier(&balance()), 2);
+        assert_eq!(economy.next_multiplier(&balance()), Some(76));
+
+        economy.stored_clicks = 10_000;
+        assert_eq!(economy.cashed_out(&balance()).unwrap().stored_clicks, 0);
+        economy.meta_upgrades.deep_pockets = 2;
+        assert_eq!(economy.cashed_out(&balance()).unwrap().stored_clicks, 10_000);
+    }
+
+    #[test]
+    fn shipped_balance_matches_the_defaults() {
+        let shipped: Balance = ron::from_str(include_str!("../assets/balance.ron")).unwrap();
+        assert_eq!(shipped, balance());
+        assert_eq!(shipped.validate(), Ok(()));
+    }
+
+    #[test]
+    fn missing_balance_fields_use_defaults() {
+        let partial: Balance = ron::from_str("(combine_cost: 5)").unwrap();
+        assert_eq!(partial, Balance { combine_cost: 5, ..balance() });
+    }
+
+    #[test]
+    fn invalid_balances_are_rejected() {
+        let invalid = |balance: Balance| balance.validate().unwrap_err();
+        assert_eq!(
+            invalid(Balance { multiplier_table: vec![40, 30], ..balance() }),
+            BalanceError::MultiplierTableNotIncreasing
+        );
+        assert_eq!(
+            invalid(Balance { cashout_table: vec![5, 5], ..balance() }),
+            BalanceError::CashoutTableNotIncreasing
+        );
+        assert_eq!(invalid(Balance { finger_cost_growth: 0.9, ..balance() }), BalanceError::FingerCostShrinks);
+        assert_eq!(invalid(Balance { finger_cost_growth: f64::NAN, ..balance() }), BalanceError::FingerCostShrinks);
+        assert_eq!(invalid(Balance { finger_refund_percent: 150, ..balance() }), BalanceError::RefundOverFullPrice);
+        assert_eq!(invalid(Balance { clap_periods: vec![], ..balance() }), BalanceError::ClapPeriodsNotDecreasing);
+        assert_eq!(
+            invalid(Balance { clap_periods: vec![1.0, 2.0, 0.5], ..balance() }),
+            BalanceError::ClapPeriodsNotDecreasing
+        );
+        assert_eq!(
+            invalid(Balance { clap_speed_costs: vec![1], ..balance() }),
+            BalanceError::ClapSpeedCostCount { expected: 2, actual: 1 }
+        );
+        assert_eq!(invalid(Balance { win_score: 0, ..balance() }), BalanceError::ZeroWinScore);
+        assert_eq!(invalid(Balance { cashout_growth: 1, ..balance() }), BalanceError::CashoutGrowthTooSmall);
+        assert_eq!(invalid(Balance { synergy_decay: 1.0, ..balance() }), BalanceError::SynergyOutOfRange);
+        assert_eq!(invalid(Balance { synergy_percent: f64::NAN, ..balance() }), BalanceError::SynergyOutOfRange);
+        assert_eq!(invalid(Balance { trickle_per_building: -1.0, ..balance() }), BalanceError::NegativeTrickle);
+    }
+
+    #[test]
+    fn trickle_grows_with_buildings() {
+        assert_eq!(Economy::default().trickle_rate(&balance()), TRICKLE_PER_BUILDING);
+        let prestiged = Economy { buildings: 4, ..Economy::default() };
+        assert_eq!(prestiged.trickle_rate(&balance()), 4.0 * TRICKLE_PER_BUILDING);
+        assert_eq!(prestiged.trickle_rate(&Balance { trickle_per_building: 0.0, ..balance() }), 0.0);
+    }
+
+    #[test]
+    fn synergy_grows_with_diminishing_returns() {
+        let balance = balance();
+        assert_eq!(balance.synergy(0), 0.0);
+        assert!((balance.synergy(1) - SYNERGY_PERCENT).abs() < 1e-9);
+        let cap = SYNERGY_PERCENT / (1.0 - SYNERGY_DECAY);
+        for autoed in 1..100 {
+            let (before, now, after) = (balance.synergy(autoed - 1), balance.synergy(autoed), balance.synergy(autoed + 1));
+            assert!(now > before && now <= cap, "{}", autoed);
+            assert!(after - now < now - before, "{}", autoed);
+        }
+        assert!(balance.synergy(u32::MAX) <= cap);
+    }
+
+    #[test]
+    fn hand_rates_follow_clap_speed() {
+        let economy = Economy { clap_speed: 1, ..Economy::default() };
+        assert_eq!(economy.hand_rate(&balance(), 3, false), 3);
+        // 0.5s claps
+        assert_eq!(economy.hand_rate(&balance(), 3, true), 6);
+    }
+
+    #[test]
+    fn net_worth_adds_an_horizon_of_income() {
+        let economy = Economy { stored_clicks: 1_000, ..Economy::default() };
+        assert_eq!(economy.net_worth(0), 1_000);
+        // a filling hand of 3 fingers and an autoed hand of 2, unupgraded
+        let income = economy.hand_rate(&balance(), 3, false) + economy.hand_rate(&balance(), 2, true);
+        assert_eq!(economy.net_worth(income), 1_000 + 5 * NET_WORTH_HORIZON_SECS);
+        // influence income counts
+        let mut boosted = economy.clone();
+        *boosted.meta_upgrades.level_mut(MetaUpgrade::Income) = 1;
+        assert_eq!(boosted.net_worth(10), 1_000 + 11 * NET_WORTH_HORIZON_SECS);
+        // right after a cashout the bank is empty but the income isn't
+        let cashed = Economy { stored_clicks: 0, buildings: 2, ..Economy::default() };
+        let income = cashed.hand_rate(&balance(), 5 * cashed.multiplier(&balance()), false);
+        assert!(cashed.net_worth(income) > economy.net_worth(5));
+        assert_eq!(Economy { stored_clicks: u64::MAX, ..Economy::default() }.net_worth(u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn difficulty_scales_prices_thresholds_and_the_win() {
+        let at = |difficulty| Economy { difficulty, ..with_fingers(1) };
+        let (easy, normal, hard) = (at(Difficulty::Easy), at(Difficulty::Normal), at(Difficulty::Hard));
+        assert_eq!(normal.finger_cost(&balance()), 10);
+        assert!(easy.finger_cost(&balance()) < normal.finger_cost(&balance()));
+        assert!(hard.finger_cost(&balance()) > normal.finger_cost(&balance()));
+        assert_eq!(easy.hand_cost(&balance()), 7);
+        assert_eq!(hard.auto_cost(&balance()), 90);
+        assert_eq!(hard.cashout_cost(&balance()), Some(15_000));
+        // influence is earned on the undiscounted price
+        assert_eq!(easy.cashout_influence(&balance()), normal.cashout_influence(&balance()));
+        assert_eq!(easy.next_multiplier(&balance()), Some(32));
+        assert_eq!(hard.next_multiplier(&balance()), Some(50));
+        assert_eq!(easy.win_score(&balance()), WIN_SCORE / 2);
+        assert_eq!(hard.win_score(&balance()), WIN_SCORE * 2);
+        // and a cashout doesn't change it
+        assert_eq!(hard.cashed_out(&balance()).map(|after| after.difficulty), Some(Difficulty::Hard));
+    }
+
+    #[test]
+    fn synergy_raises_clicks() {
+        assert_eq!(with_synergy(1_000, 0.0), 1_000);
+        assert_eq!(with_synergy(1_000, 5.0), 1_050);
+        assert_eq!(with_synergy(u64::MAX, 25.0), u64::MAX);
+    }
+}
@@ -0,0 +1,1070 @@
+use std::{collections::VecDeque, time::Duration};
+
+use bevy::{prelude::*, utils::HashMap};
+use rand::Rng;
+
+use super::{with_synergy, Balance, Economy, MetaUpgrade, MAX_HANDS_PER_PURCHASE};
+use crate::{
+    balance::BalanceConfig,
+    components::{
+        clap_clicks, default_hand_name, default_hand_number, spawn_hand, AutoBuy, Clicker, ClickerLabel, ClickerQuery, ClickerState, HandIncome,
+        HandName, HandState, HandStats, ReadyTime, TillCanClickTimer, IDLE_FINGER_SECS,
+    },
+    locale::Locale,
+    resources::{
+        play_sound, ClickRate, ClickSource, ClicksEmitted, Combo, EventLog, FastForward, GameConfig, GameRng, Income, IncomeBreakdown, Score,
+        Settings, Sounds, Statistics, Toasts, Trickle,
+    },
+    states::State,
+    ui::PlayerInput,
+};
+
+// scoring, purchases and everything else that moves clicks around
+pub struct EconomyPlugin;
+
+impl Plugin for EconomyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ClicksEmitted>()
+            .add_event::<Purchase>()
+            .add_event::<PurchaseApplied>()
+            .add_event::<ClickReadyFingers>()
+            .add_event::<MultiplierRaised>()
+            .insert_resource(ClickRate::default())
+            .insert_resource(CpsHistory::default())
+            .insert_resource(Combo::default())
+            .init_resource::<GoldenClickSpawner>()
+            .init_resource::<FastForward>()
+            .init_resource::<Income>()
+            .init_resource::<Trickle>()
+            .init_resource::<EventLog>()
+            .add_systems(Update, (
+                compute_income_system.before(PlayerInput),
+                combo_decay_system,
+                click_ready_fingers_system.after(PlayerInput),
+                auto_buy_system.after(PlayerInput),
+                apply_purchase_system.after(PlayerInput).after(auto_buy_system),
+                play_time_system,
+                achievements_system,
+                multiplier_tier_system,
+                event_log_system.after(multiplier_tier_system),
+                golden_click_spawn_system,
+                golden_click_timeout_system,
+                update_timers_system,
+                auto_clap_system.after(update_timers_system),
+                trickle_system,
+                collect_score_system,
+                hand_stats_system.after(update_timers_system),
+                click_rate_system,
+                cps_history_system,
+            ).run_if(in_state(State::Game)));
+    }
+}
+
+// before the ui, which shows it and clicks with it. purchases land after the
+// ui, so they're picked up on the next frame.
+pub fn compute_income_system(
+    mut hands: Query<(&HandState, &Children, &mut HandIncome)>,
+    all_clickers: ClickerQuery,
+    score: Res<Score>,
+    balance: Res<BalanceConfig>,
+    mut income: ResMut<Income>,
+) {
+    let multiplier = score.multiplier(&balance);
+    let autoed = hands.iter().filter(|(hand, ..)| matches!(hand, HandState::Autoed)).count() as u32;
+    let mut per_second = 0u64;
+    for (hand, clickers, mut hand_income) in &mut hands {
+        // autoed hands boost every hand but themselves
+        let synergy = balance.synergy(autoed - matches!(hand, HandState::Autoed) as u32);
+        let per_clap = with_synergy(clap_clicks(clickers, &all_clickers, multiplier), synergy);
+        let hand_per_second = score.hand_rate(&balance, per_clap, !matches!(hand, HandState::Filling));
+        *hand_income = HandIncome { synergy, per_clap, per_second: hand_per_second };
+        per_second = per_second.saturating_add(hand_per_second);
+    }
+    *income = Income { multiplier, per_second };
+}
+
+// purchases across all hands, so auto-buy can't snowball in a single frame
+const AUTO_BUY_PER_SECOND : f32 = 4.0;
+
+fn combo_decay_system(mut combo: ResMut<Combo>, time: Res<Time>) {
+    if combo.streak == 0 {
+        return;
+    }
+    if combo.decay.tick(time.delta()).just_finished() {
+        *combo = Combo::default();
+    }
+}
+
+// name and description are looked up as achievement.<id>.name/.description
+pub struct Achievement {
+    id: &'static str,
+    predicate: fn(&Economy, &Balance, &GameConfig) -> bool,
+    pub unlocked: bool,
+}
+
+#[derive(Resource)]
+pub struct Achievements(pub Vec<Achievement>);
+
+impl Achievements {
+    pub fn from_unlocked(unlocked: &[String]) -> Self {
+        let achievement = |id, predicate| Achievement {
+            id,
+            predicate,
+            unlocked: unlocked.iter().any(|u| u == id),
+        };
+        Achievements(vec![
+            achievement("first_hand", |e, _, _| e.hands_owned >= 1),
+            achievement("hundred_fingers", |e, _, _| e.total_fingers >= 100),
+            achievement("first_cashout", |e, _, _| e.buildings >= 2),
+            achievement("multiplier_1024", |e, b, _| e.multiplier(b) >= 1024),
+            // the game's own goal, whatever difficulty and length it was started with
+            achievement("trillion_clicks", |e, b, c| !e.sandbox && e.stored_clicks >= c.win_score(e, b)),
+        ])
+    }
+
+    pub fn unlocked_ids(&self) -> Vec<String> {
+        self.0.iter().filter(|a| a.unlocked).map(|a| a.id.to_string()).collect()
+    }
+}
+
+impl Achievement {
+    pub fn name(&self, locale: &Locale) -> String {
+        locale.t(&format!("achievement.{}.name", self.id))
+    }
+
+    pub fn description(&self, locale: &Locale) -> String {
+        locale.t(&format!("achievement.{}.description", self.id))
+    }
+}
+
+// one sample a second for the last two minutes
+pub const CPS_HISTORY_LEN : usize = 120;
+const CPS_SAMPLE_SECS : f32 = 1.0;
+
+#[derive(Resource)]
+pub struct CpsHistory {
+    pub samples: VecDeque<f64>,
+    timer: Timer,
+    pub paused: bool,
+}
+
+impl Default for CpsHistory {
+    fn default() -> Self {
+        CpsHistory {
+            samples: VecDeque::with_capacity(CPS_HISTORY_LEN),
+            timer: Timer::from_seconds(CPS_SAMPLE_SECS, TimerMode::Repeating),
+            paused: false,
+        }
+    }
+}
+
+impl CpsHistory {
+    fn record(&mut self, rate: f64) {
+        if self.samples.len() == CPS_HISTORY_LEN {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(rate);
+    }
+}
+
+fn cps_history_system(
+    mut history: ResMut<CpsHistory>,
+    click_rate: Res<ClickRate>,
+    time: Res<Time>,
+) {
+    if history.paused {
+        return;
+    }
+    if history.timer.tick(time.delta()).just_finished() {
+        history.record(click_rate.rate());
+    }
+}
+
+fn click_rate_system(
+    mut click_rate: ResMut<ClickRate>,
+    mut income: ResMut<IncomeBreakdown>,
+    mut statistics: ResMut<Statistics>,
+    mut clicker_events: EventReader<ClicksEmitted>,
+    score: Res<Score>,
+    time: Res<Time>,
+) {
+    // rates count what lands in the bank, influence boost included
+    let mut by_source = [0u64; ClickSource::ALL.len()];
+    for ClicksEmitted { amount, source, .. } in clicker_events.read() {
+        by_source[*source as usize] = by_source[*source as usize].saturating_add(score.boosted(*amount));
+    }
+    let clicks = by_source.iter().fold(0u64, |total, clicks| total.saturating_add(*clicks));
+    click_rate.advance(time.delta_seconds(), clicks);
+    statistics.peak_cps = statistics.peak_cps.max(click_rate.rate());
+    for (rate, clicks) in income.rates.iter_mut().zip(by_source) {
+        rate.advance(time.delta_seconds(), clicks);
+    }
+}
+
+// sends whole clicks as the buildings' passive income adds up to them,
+// keeping the rest for the next frame
+pub fn trickle_system(
+    mut trickle: ResMut<Trickle>,
+    mut clicker_events: EventWriter<ClicksEmitted>,
+    score: Res<Score>,
+    balance: Res<BalanceConfig>,
+    fast_forward: Res<FastForward>,
+    time: Res<Time>,
+) {
+    let earned = trickle.fraction + score.trickle_rate(&balance) * fast_forward.scale(time.delta()).as_secs_f64();
+    let whole = earned.floor();
+    trickle.fraction = earned - whole;
+    if whole >= 1.0 {
+        clicker_events.send(ClicksEmitted { amount: whole as u64, source: ClickSource::Trickle, hand: None });
+    }
+}
+
+// at most this many click sounds per second, no matter how many clicks land
+const CLICK_SOUNDS_PER_SECOND : f32 = 4.0;
+
+#[allow(clippy::too_many_arguments)]
+pub fn collect_score_system(
+    mut score: ResMut<Score>,
+    mut clicker_events: EventReader<ClicksEmitted>,
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<State>>,
+    sounds: Res<Sounds>,
+    time: Res<Time>,
+    mut last_click_sound: Local<Option<f32>>,
+    mut statistics: ResMut<Statistics>,
+    mut income: ResMut<IncomeBreakdown>,
+    balance: Res<BalanceConfig>,
+    config: Res<GameConfig>,
+) {
+    for ClicksEmitted { amount, source, .. } in clicker_events.read() {
+        let amount = score.boosted(*amount);
+        score.stored_clicks = score.stored_clicks.saturating_add(amount);
+        statistics.lifetime_clicks = statistics.lifetime_clicks.saturating_add(amount);
+        income.add(*source, amount);
+        let now = time.elapsed_seconds();
+        // nobody clicked for the trickle
+        if *source != ClickSource::Trickle && last_click_sound.is_none_or(|last| now - last >= 1.0 / CLICK_SOUNDS_PER_SECOND) {
+            play_sound(&mut commands, sounds.click.as_ref());
+            *last_click_sound = Some(now);
+        }
+    }
+    if !score.sandbox && score.stored_clicks >= config.win_score(&score, &balance) {
+        next_state.set(State::Finished);
+    }
+}
+
+// clicks every ready finger on filling hands, same as pressing each "Click"
+// button once. Combined and autoed hands are left alone since they already
+// clap on their own.
+#[derive(Event)]
+pub struct ClickReadyFingers;
+
+fn click_ready_fingers_system(
+    mut requests: EventReader<ClickReadyFingers>,
+    hands: Query<(Entity, &HandState, &Children, &HandIncome)>,
+    mut all_clickers: Query<(&ClickerState, &mut TillCanClickTimer), With<ClickerLabel>>,
+    income: Res<Income>,
+    mut clicker_events: EventWriter<ClicksEmitted>,
+    mut combo: ResMut<Combo>,
+) {
+    if requests.read().count() == 0 {
+        return;
+    }
+    // the whole press counts as one hit on the combo
+    let bonus = combo.bonus();
+    let mut first_clicked = None;
+    for (hand_entity, hand, clickers, hand_income) in &hands {
+        if !matches!(hand, HandState::Filling) {
+            continue;
+        }
+        for clicker in clickers.iter() {
+            let Ok((state, mut timer)) = all_clickers.get_mut(*clicker) else {
+                continue;
+            };
+            if timer.0.finished() {
+                timer.0.reset();
+                let amount = with_synergy(state.per_click.saturating_mul(income.multiplier), hand_income.synergy).saturating_mul(bonus);
+                clicker_events.send(ClicksEmitted { amount, source: ClickSource::Manual, hand: Some(hand_entity) });
+                first_clicked.get_or_insert(hand_entity);
+            }
+        }
+    }
+    if let Some(hand_entity) = first_clicked {
+        combo.hit(hand_entity);
+    }
+}
+
+const GOLDEN_CLICK_MIN_DELAY_SECS : f32 = 30.0;
+const GOLDEN_CLICK_MAX_DELAY_SECS : f32 = 90.0;
+const GOLDEN_CLICK_LIFETIME_SECS : f32 = 5.0;
+// a golden click pays out this many seconds of current production
+const GOLDEN_CLICK_REWARD_SECS : u64 = 30;
+
+pub fn golden_click_delay(rng: &mut GameRng) -> Timer {
+    let secs = rng.rng.gen_range(GOLDEN_CLICK_MIN_DELAY_SECS..GOLDEN_CLICK_MAX_DELAY_SECS);
+    Timer::from_seconds(secs, TimerMode::Once)
+}
+
+#[derive(Resource)]
+pub struct GoldenClickSpawner(pub Timer);
+
+impl FromWorld for GoldenClickSpawner {
+    fn from_world(world: &mut World) -> Self {
+        GoldenClickSpawner(golden_click_delay(&mut world.resource_mut::<GameRng>()))
+    }
+}
+
+#[derive(Component)]
+pub struct GoldenClick {
+    lifetime: Timer,
+    // fraction of the screen, resolved against the egui screen rect when drawn
+    pub position: Vec2,
+}
+
+pub fn golden_click_reward(clicks_per_second: f64, multiplier: u64) -> u64 {
+    let production = (clicks_per_second.round() as u64).max(multiplier);
+    production.saturating_mul(GOLDEN_CLICK_REWARD_SECS)
+}
+
+fn golden_click_spawn_system(
+    mut spawner: ResMut<GoldenClickSpawner>,
+    mut rng: ResMut<GameRng>,
+    mut commands: Commands,
+    time: Res<Time>,
+) {
+    if !spawner.0.tick(time.delta()).just_finished() {
+        return;
+    }
+    let position = Vec2::new(rng.rng.gen_range(0.1..0.9), rng.rng.gen_range(0.1..0.9));
+    commands.spawn(GoldenClick {
+        lifetime: Timer::from_seconds(GOLDEN_CLICK_LIFETIME_SECS, TimerMode::Once),
+        position,
+    });
+    spawner.0 = golden_click_delay(&mut rng);
+}
+
+// clicking and timing out can land in the same frame, and whichever is
+// applied second finds the golden click already gone
+pub fn despawn_golden_click(commands: &mut Commands, entity: Entity) {
+    commands.add(move |world: &mut World| {
+        if let Some(golden) = world.get_entity_mut(entity) {
+            golden.despawn();
+        }
+    });
+}
+
+fn golden_click_timeout_system(
+    mut golden_clicks: Query<(Entity, &mut GoldenClick)>,
+    mut commands: Commands,
+    time: Res<Time>,
+) {
+    for (entity, mut golden) in golden_clicks.iter_mut() {
+        if golden.lifetime.tick(time.delta()).just_finished() {
+            despawn_golden_click(&mut commands, entity);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn achievements_system(
+    score: Res<Score>,
+    mut achievements: ResMut<Achievements>,
+    mut toasts: ResMut<Toasts>,
+    mut log: ResMut<EventLog>,
+    statistics: Res<Statistics>,
+    balance: Res<BalanceConfig>,
+    config: Res<GameConfig>,
+    locale: Res<Locale>,
+) {
+    for achievement in achievements.0.iter_mut() {
+        if !achievement.unlocked && (achievement.predicate)(&score, &balance, &config) {
+            achievement.unlocked = true;
+            let name = achievement.name(&locale);
+            toasts.push(locale.tf("achievements.unlocked", &[("name", &name)]));
+            log.push(statistics.play_time, locale.tf("log.achievement", &[("name", &name)]));
+        }
+    }
+}
+
+// the multiplier went up a tier from buying fingers
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct MultiplierRaised {
+    pub multiplier: u64,
+}
+
+// a cashout changes the multiplier too, but it has its own fanfare, so only
+// a rise with the same buildings counts
+fn multiplier_tier_system(
+    score: Res<Score>,
+    balance: Res<BalanceConfig>,
+    mut raised_events: EventWriter<MultiplierRaised>,
+    mut last: Local<Option<(u64, u32)>>,
+) {
+    let multiplier = score.multiplier(&balance);
+    if let Some((before, buildings)) = last.replace((multiplier, score.buildings)) {
+        if multiplier > before && buildings == score.buildings {
+            raised_events.send(MultiplierRaised { multiplier });
+        }
+    }
+}
+
+// tier ups and golden clicks. purchases are logged as they're applied,
+// since only then is it known whether they went through.
+fn event_log_system(
+    mut raised_events: EventReader<MultiplierRaised>,
+    mut clicker_events: EventReader<ClicksEmitted>,
+    mut log: ResMut<EventLog>,
+    statistics: Res<Statistics>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+) {
+    for MultiplierRaised { multiplier } in raised_events.read() {
+        log.push(statistics.play_time, locale.tf("log.tier", &[("multiplier", &settings.format(*multiplier))]));
+    }
+    for ClicksEmitted { amount, source, .. } in clicker_events.read() {
+        if *source == ClickSource::Golden {
+            log.push(statistics.play_time, locale.tf("log.golden", &[("clicks", &settings.format(*amount))]));
+        }
+    }
+}
+
+pub fn play_time_system(mut statistics: ResMut<Statistics>, time: Res<Time>) {
+    statistics.play_time = statistics.play_time.saturating_add(time.delta());
+}
+
+pub fn update_timers_system(
+    mut all_clickers: Query<(&mut TillCanClickTimer, Option<&HandState>, Option<&mut ReadyTime>)>,
+    score: Res<Score>,
+    balance: Res<BalanceConfig>,
+    fast_forward: Res<FastForward>,
+    time: Res<Time>,
+) {
+    let delta = fast_forward.scale(time.delta());
+    // hands pick up the current clap speed, including ones spawned since the purchase
+    let clap_period = Duration::from_secs_f32(score.clap_period(&balance));
+    for (mut timer, hand, ready_time) in &mut all_clickers.iter_mut() {
+        if hand.is_some() && timer.0.duration() != clap_period {
+            timer.0.set_duration(clap_period);
+        }
+        // autoed hands never wait to be clicked, so their timers keep
+        // running and a long frame can finish several claps at once
+        let mode = if matches!(hand, Some(HandState::Autoed)) { TimerMode::Repeating } else { TimerMode::Once };
+        if timer.0.mode() != mode {
+            timer.0.set_mode(mode);
+        }
+        timer.0.tick(delta);
+        if let Some(mut ready_time) = ready_time {
+            ready_time.0 = if timer.0.finished() { ready_time.0 + delta } else { Duration::ZERO };
+        }
+    }
+}
+
+// autoed hands clap as often as their timers finished this frame, whether or
+// not their window is drawn
+pub fn auto_clap_system(
+    hands: Query<(Entity, &HandState, &TillCanClickTimer, &HandIncome)>,
+    mut clicker_events: EventWriter<ClicksEmitted>,
+) {
+    for (hand_entity, hand, timer, hand_income) in &hands {
+        let claps = timer.0.times_finished_this_tick() as u64;
+        if matches!(hand, HandState::Autoed) && claps > 0 {
+            let amount = hand_income.per_clap.saturating_mul(claps);
+            clicker_events.send(ClicksEmitted { amount, source: ClickSource::Auto, hand: Some(hand_entity) });
+        }
+    }
+}
+
+// credits each hand with the clicks it banked, influence boost included, and
+// flags the idle ones
+fn hand_stats_system(
+    mut clicker_events: EventReader<ClicksEmitted>,
+    mut hands: Query<(&HandState, &Children, &mut HandStats)>,
+    ready_times: Query<&ReadyTime>,
+    score: Res<Score>,
+) {
+    for ClicksEmitted { amount, hand, .. } in clicker_events.read() {
+        if let Some(Ok((_, _, mut stats))) = hand.map(|hand| hands.get_mut(hand)) {
+            stats.emitted = stats.emitted.saturating_add(score.boosted(*amount));
+        }
+    }
+    let idle_after = Duration::from_secs_f32(IDLE_FINGER_SECS);
+    for (state, clickers, mut stats) in &mut hands {
+        let idle = matches!(state, HandState::Filling)
+            && ready_times.iter_many(clickers).any(|ready_time| ready_time.0 >= idle_after);
+        if stats.idle != idle {
+            stats.idle = idle;
+        }
+    }
+}
+
+// runs after the ui so manual purchases get first pick of the clicks. each
+// tick buys one finger for the enabled hand that has the fewest.
+fn auto_buy_system(
+    hands: Query<(Entity, &HandState, &Children, &AutoBuy)>,
+    score: Res<Score>,
+    mut purchases: EventWriter<Purchase>,
+    mut throttle: Local<Option<Timer>>,
+    time: Res<Time>,
+    balance: Res<BalanceConfig>,
+) {
+    let throttle = throttle.get_or_insert_with(|| {
+        Timer::from_seconds(1.0 / AUTO_BUY_PER_SECOND, TimerMode::Repeating)
+    });
+    if !throttle.tick(time.delta()).just_finished() {
+        return;
+    }
+    if score.stored_clicks < score.finger_cost(&balance) {
+        return;
+    }
+    let neediest = hands.iter()
+        .filter(|(_, hand, clickers, auto_buy)| {
+            matches!(hand, HandState::Filling) && auto_buy.enabled && (clickers.len() as u32) < auto_buy.cap
+        })
+        .min_by_key(|(_, _, clickers, _)| clickers.len());
+    if let Some((hand_entity, ..)) = neediest {
+        purchases.send(Purchase::Finger { hand: hand_entity, count: 1 });
+    }
+}
+
+// everything the store and hand windows can buy. the ui only sends these,
+// apply_purchase_system does the spending so other systems can listen in.
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub enum Purchase {
+    // buys up to `count` fingers, stopping early if they stop being affordable
+    Finger { hand: Entity, count: u64 },
+    // the hand's newest finger, for half what it cost. never its last one.
+    SellFinger { hand: Entity },
+    // up to `count` hands, the same way
+    Hand { count: u64 },
+    Combine { hand: Entity },
+    Auto { hand: Entity },
+    FingerUpgrade,
+    ClapSpeed,
+    Cashout,
+    Meta(MetaUpgrade),
+}
+
+// a purchase that went through, for systems that care what was bought
+// rather than what was asked for
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct PurchaseApplied(pub Purchase);
+
+// prices are checked again here since an earlier purchase this frame may
+// have spent the clicks
+#[allow(clippy::too_many_arguments)]
+pub fn apply_purchase_system(
+    mut purchases: EventReader<Purchase>,
+    mut applied: EventWriter<PurchaseApplied>,
+    mut commands: Commands,
+    mut score: ResMut<Score>,
+    mut hands: Query<(Entity, &mut HandState, &HandName)>,
+    fingers: Query<&Children>,
+    mut all_clickers: Query<&mut ClickerState>,
+    sounds: Res<Sounds>,
+    balance: Res<BalanceConfig>,
+    locale: Res<Locale>,
+    mut log: ResMut<EventLog>,
+    statistics: Res<Statistics>,
+    settings: Res<Settings>,
+    config: Res<GameConfig>,
+) {
+    // numbered past the highest default name in use, so a new hand never
+    // repeats one. spawns are deferred, so hands bought this frame are
+    // counted here too.
+    let mut next_index = hands.iter().filter_map(|(.., name)| default_hand_number(&locale, &name.0)).max().unwrap_or(0);
+    // a cashout despawns every hand in `hands`, but only once the commands
+    // are applied, so later purchases this frame mustn't touch them
+    let mut cashed_out = false;
+    // fingers sold off each hand this frame, still among its children until
+    // the despawns are applied
+    let mut sold: HashMap<Entity, usize> = HashMap::new();
+    for purchase in purchases.read() {
+        let mut sound = sounds.purchase.as_ref();
+        let stored = score.stored_clicks;
+        let entry = match *purchase {
+            Purchase::Finger { hand, count } => {
+                // the hand may have combined or been cashed out since the request
+                if cashed_out || !hands.get(hand).is_ok_and(|(_, state, _)| matches!(state, HandState::Filling)) {
+                    continue;
+                }
+                let mut bought = 0;
+                while bought < count && score.stored_clicks >= score.finger_cost(&balance) {
+                    commands.spawn(Clicker::with_per_click(score.per_click())).set_parent(hand);
+                    score.stored_clicks -= score.finger_cost(&balance);
+                    score.total_fingers += 1;
+                    bought += 1;
+                }
+                if bought == 0 {
+                    continue;
+                }
+                locale.tf("log.fingers", &[("count", &bought)])
+            }
+            Purchase::SellFinger { hand } => {
+                if cashed_out || !hands.get(hand).is_ok_and(|(_, state, _)| matches!(state, HandState::Filling)) {
+                    continue;
+                }
+                let sold_here = sold.entry(hand).or_default();
+                let clickers = fingers.get(hand).map_or(&[][..], |children| &children[..]);
+                let left = &clickers[..clickers.len().saturating_sub(*sold_here)];
+                let (Some(newest), Some(refund)) = (left.last(), score.sell_finger(&balance, left.len() as u64)) else {
+                    continue;
+                };
+                commands.entity(*newest).despawn_recursive();
+                *sold_here += 1;
+                locale.tf("log.sell_finger", &[("clicks", &settings.format(refund))])
+            }
+            Purchase::Hand { count } => {
+                let mut bought = 0;
+                while bought < count.min(MAX_HANDS_PER_PURCHASE) && score.stored_clicks >= score.hand_cost(&balance) {
+                    spawn_hand(&mut commands, default_hand_name(&locale, next_index), HandState::Filling, 0, score.per_click());
+                    next_index += 1;
+                    score.stored_clicks -= score.hand_cost(&balance);
+                    score.hands_owned += 1;
+                    bought += 1;
+                }
+                if bought == 0 {
+                    continue;
+                }
+                locale.tf("log.hands", &[("count", &bought)])
+            }
+            Purchase::Combine { hand } => {
+                let Ok((_, mut state, _)) = hands.get_mut(hand) else { continue };
+                if cashed_out || !matches!(*state, HandState::Filling) || score.stored_clicks < score.combine_cost(&balance) {
+                    continue;
+                }
+                *state = HandState::Combined;
+                score.stored_clicks -= score.combine_cost(&balance);
+                score.hands_combined += 1;
+                locale.t("log.combine")
+            }
+            Purchase::Auto { hand } => {
+                let Ok((_, mut state, _)) = hands.get_mut(hand) else { continue };
+                if cashed_out || !matches!(*state, HandState::Combined) || score.stored_clicks < score.auto_cost(&balance) {
+                    continue;
+                }
+                *state = HandState::Autoed;
+                score.stored_clicks -= score.auto_cost(&balance);
+                locale.t("log.auto")
+            }
+            Purchase::FingerUpgrade => {
+                let cost = score.finger_upgrade_cost(&balance);
+                if score.stored_clicks < cost {
+                    continue;
+                }
+                score.stored_clicks -= cost;
+                score.finger_upgrades += 1;
+                for mut state in all_clickers.iter_mut() {
+                    state.per_click = score.per_click();
+                }
+                locale.tf("log.finger_upgrade", &[("level", &score.finger_upgrades)])
+            }
+            Purchase::ClapSpeed => {
+                let Some(cost) = score.clap_speed_cost(&balance).filter(|cost| score.stored_clicks >= *cost) else { continue };
+                score.stored_clicks -= cost;
+                score.clap_speed += 1;
+                locale.tf("log.clap_speed", &[("level", &score.clap_speed)])
+            }
+            Purchase::Cashout => {
+                let Some(after) = score.cashed_out(&balance).filter(|_| score.can_cash_out(&balance)) else { continue };
+                score.0 = after;
+                // delete all the hands, unless an earlier cashout this frame already has
+                if !cashed_out {
+                    for (hand, ..) in &hands {
+                        commands.entity(hand).despawn_recursive();
+                    }
+                }
+                cashed_out = true;
+                // return to initial state, with the fingers the game started with
+                score.total_fingers = config.first_hand_fingers() as u64;
+                spawn_hand(&mut commands, default_hand_name(&locale, 0), HandState::Filling, config.first_hand_fingers(), score.per_click());
+                next_index = 1;
+                sound = sounds.cashout.as_ref();
+                locale.tf("log.cashout", &[("buildings", &score.buildings)])
+            }
+            Purchase::Meta(upgrade) => {
+                if !score.buy_meta_upgrade(&balance, upgrade) {
+                    continue;
+                }
+                locale.tf("log.meta", &[("name", &locale.t(&format!("influence.{}.name", upgrade.key())))])
+            }
+        };
+        let spent = stored.saturating_sub(score.stored_clicks);
+        log.push(statistics.play_time, if spent > 0 { locale.tf("log.spent", &[("entry", &entry), ("clicks", &settings.format(spent))]) } else { entry });
+        play_sound(&mut commands, sound);
+        applied.send(PurchaseApplied(*purchase));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn golden_click_timing_is_reproducible_from_a_seed() {
+        let mut a = GameRng::from_seed(42);
+        let mut b = GameRng::from_seed(42);
+        for _ in 0..10 {
+            let delay = golden_click_delay(&mut a).duration();
+            assert_eq!(delay, golden_click_delay(&mut b).duration());
+            assert!(delay.as_secs_f32() >= GOLDEN_CLICK_MIN_DELAY_SECS);
+            assert!(delay.as_secs_f32() < GOLDEN_CLICK_MAX_DELAY_SECS);
+        }
+    }
+
+    #[test]
+    fn golden_clicks_go_when_clicked_or_timed_out() {
+        let mut app = crate::testing::manual_clock_app(Duration::from_secs(1));
+        app.add_systems(Update, golden_click_timeout_system);
+        let golden = |secs| GoldenClick { lifetime: Timer::from_seconds(secs, TimerMode::Once), position: Vec2::ZERO };
+        let lasting = app.world.spawn(golden(GOLDEN_CLICK_LIFETIME_SECS)).id();
+        let expiring = app.world.spawn(golden(1.0)).id();
+        let clicked = app.world.spawn(golden(GOLDEN_CLICK_LIFETIME_SECS)).id();
+        // one is clicked in the frame it also times out
+        app.add_systems(Update, move |mut commands: Commands| {
+            despawn_golden_click(&mut commands, clicked);
+            despawn_golden_click(&mut commands, expiring);
+        });
+        app.update();
+        assert!(app.world.get_entity(lasting).is_some());
+        assert!(app.world.get_entity(expiring).is_none());
+        assert!(app.world.get_entity(clicked).is_none());
+    }
+
+    #[test]
+    fn winning_a_short_game_unlocks_the_win_achievement() {
+        let mut app = crate::testing::test_app();
+        app.insert_resource(Achievements::from_unlocked(&[]))
+            .init_resource::<Toasts>()
+            .init_resource::<Locale>()
+            .insert_resource(GameConfig { win_percent: 10, ..GameConfig::default() })
+            .add_systems(Update, achievements_system);
+        let win_score = app.world.resource::<GameConfig>().win_score(app.world.resource::<Score>(), app.world.resource::<BalanceConfig>());
+        assert!(win_score < app.world.resource::<BalanceConfig>().win_score);
+        app.world.resource_mut::<Score>().stored_clicks = win_score;
+        app.update();
+        let unlocked = app.world.resource::<Achievements>().unlocked_ids();
+        assert!(unlocked.contains(&"trillion_clicks".to_string()));
+    }
+
+    #[test]
+    fn golden_click_reward_pays_out_production() {
+        assert_eq!(golden_click_reward(0.0, 1), 30);
+        assert_eq!(golden_click_reward(100.0, 4), 3_000);
+    }
+
+    fn scoring_app() -> App {
+        let mut app = crate::testing::test_app();
+        app.add_systems(Update, collect_score_system);
+        app
+    }
+
+    #[test]
+    fn collected_clicks_accumulate() {
+        let mut app = scoring_app();
+        app.world.send_event(ClicksEmitted { amount: 40, source: ClickSource::Manual, hand: None });
+        app.world.send_event(ClicksEmitted { amount: 2, source: ClickSource::Golden, hand: None });
+        app.update();
+        app.world.send_event(ClicksEmitted { amount: 8, source: ClickSource::Auto, hand: None });
+        app.update();
+
+        assert_eq!(app.world.resource::<Score>().stored_clicks, 50);
+        assert_eq!(app.world.resource::<Statistics>().lifetime_clicks, 50);
+        let income = app.world.resource::<IncomeBreakdown>();
+        assert_eq!(income.total(ClickSource::Manual), 40);
+        assert_eq!(income.total(ClickSource::Golden), 2);
+        assert_eq!(income.total(ClickSource::Auto), 8);
+        assert_eq!(crate::testing::next_state(&app), None);
+    }
+
+    #[test]
+    fn reaching_the_win_score_finishes_the_game() {
+        let mut app = scoring_app();
+        let win_score = app.world.resource::<GameConfig>().win_score(app.world.resource::<Score>(), app.world.resource::<BalanceConfig>());
+        app.world.resource_mut::<Score>().stored_clicks = win_score - 1;
+        app.update();
+        assert_eq!(crate::testing::next_state(&app), None);
+
+        app.world.send_event(ClicksEmitted { amount: 1, source: ClickSource::Manual, hand: None });
+        app.update();
+        assert_eq!(crate::testing::next_state(&app), Some(State::Finished));
+    }
+
+    #[test]
+    fn sandbox_games_never_finish() {
+        let mut app = scoring_app();
+        app.world.resource_mut::<Score>().sandbox = true;
+        app.world.send_event(ClicksEmitted { amount: u64::MAX, source: ClickSource::Manual, hand: None });
+        app.update();
+        assert_eq!(crate::testing::next_state(&app), None);
+    }
+
+    #[test]
+    fn hands_are_credited_and_flagged_idle() {
+        let mut app = App::new();
+        app.add_event::<ClicksEmitted>().init_resource::<Score>().add_systems(Update, hand_stats_system);
+        let waiting = app.world.spawn(ReadyTime(Duration::from_secs_f32(IDLE_FINGER_SECS + 1.0))).id();
+        let fresh = app.world.spawn(ReadyTime::default()).id();
+        let idle_hand = app.world.spawn((HandState::Filling, HandStats::default())).push_children(&[waiting]).id();
+        let busy_hand = app.world.spawn((HandState::Filling, HandStats::default())).push_children(&[fresh]).id();
+        app.world.send_event(ClicksEmitted { amount: 7, source: ClickSource::Manual, hand: Some(busy_hand) });
+        app.world.send_event(ClicksEmitted { amount: 5, source: ClickSource::Golden, hand: None });
+        app.update();
+
+        let stats = |hand| app.world.get::<HandStats>(hand).map(|stats| (stats.emitted, stats.idle));
+        assert_eq!(stats(idle_hand), Some((0, true)));
+        assert_eq!(stats(busy_hand), Some((7, false)));
+
+        *app.world.resource_mut::<Score>().meta_upgrades.level_mut(MetaUpgrade::Income) = 1;
+        let boosted = app.world.resource::<Score>().boosted(100);
+        assert!(boosted > 100);
+        app.world.send_event(ClicksEmitted { amount: 100, source: ClickSource::Manual, hand: Some(busy_hand) });
+        app.update();
+        let stats = |hand| app.world.get::<HandStats>(hand).map(|stats| stats.emitted);
+        assert_eq!(stats(busy_hand), Some(7 + boosted));
+    }
+
+    #[test]
+    fn combining_a_bought_hand_counts_it_once() {
+        let mut app = crate::testing::purchase_app();
+        app.world.resource_mut::<Score>().stored_clicks = 1_000_000;
+        app.world.send_event(Purchase::Hand { count: 1 });
+        app.update();
+        let hand = app.world.query::<(Entity, &HandState)>().iter(&app.world).next().unwrap().0;
+        app.world.send_event(Purchase::Combine { hand });
+        app.update();
+
+        assert!(matches!(app.world.get::<HandState>(hand), Some(HandState::Combined)));
+        let score = app.world.resource::<Score>();
+        assert_eq!(score.hands_owned, 1);
+        assert_eq!(score.hands_combined, 1);
+        assert_eq!(score.hand_cost(app.world.resource::<BalanceConfig>()), 20);
+    }
+
+    #[test]
+    fn purchases_after_a_cashout_skip_the_hands_it_cleared() {
+        let mut app = crate::testing::purchase_app();
+        app.world.resource_mut::<Score>().stored_clicks = u64::MAX / 2;
+        app.world.send_event(Purchase::Hand { count: 2 });
+        app.update();
+        let hands: Vec<Entity> = app.world.query_filtered::<Entity, With<HandName>>().iter(&app.world).collect();
+        app.world.send_event(Purchase::Combine { hand: hands[1] });
+        app.update();
+        app.world.send_event(Purchase::Cashout);
+        app.world.send_event(Purchase::Finger { hand: hands[0], count: 1 });
+        app.world.send_event(Purchase::Combine { hand: hands[0] });
+        app.world.send_event(Purchase::Auto { hand: hands[1] });
+        app.update();
+
+        let left: Vec<Entity> = app.world.query_filtered::<Entity, With<HandName>>().iter(&app.world).collect();
+        assert_eq!(left.len(), 1);
+        assert!(!hands.contains(&left[0]));
+        assert_eq!(app.world.resource::<Score>().buildings, 2);
+    }
+
+    #[test]
+    fn cashing_out_starts_over_with_the_games_fingers() {
+        let mut app = crate::testing::purchase_app();
+        app.insert_resource(GameConfig { starting_fingers: 3, ..GameConfig::default() });
+        app.world.resource_mut::<Score>().stored_clicks = u64::MAX / 2;
+        app.world.send_event(Purchase::Hand { count: 1 });
+        app.update();
+        app.world.send_event(Purchase::Cashout);
+        app.update();
+
+        assert_eq!(app.world.resource::<Score>().buildings, 2);
+        assert_eq!(app.world.resource::<Score>().total_fingers, 3);
+        assert_eq!(app.world.query::<&ClickerState>().iter(&app.world).count(), 3);
+    }
+
+    #[test]
+    fn selling_takes_one_finger_and_never_the_last() {
+        let mut app = crate::testing::purchase_app();
+        let mut queue = bevy::ecs::system::CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &app.world);
+        let hand = spawn_hand(&mut commands, String::new(), HandState::Filling, 3, 1);
+        queue.apply(&mut app.world);
+        app.world.resource_mut::<Score>().total_fingers = 3;
+        let refunds = {
+            let (score, balance) = (app.world.resource::<Score>(), app.world.resource::<BalanceConfig>());
+            score.finger_refund(balance) + Economy { total_fingers: 2, ..score.0.clone() }.finger_refund(balance)
+        };
+        let fingers = |app: &mut App| app.world.query::<&ClickerState>().iter(&app.world).count();
+
+        // each sale takes a different finger, and the third would be the last
+        for _ in 0..3 {
+            app.world.send_event(Purchase::SellFinger { hand });
+        }
+        app.update();
+        assert_eq!(fingers(&mut app), 1);
+        let score = app.world.resource::<Score>();
+        assert_eq!((score.total_fingers, score.stored_clicks), (1, refunds));
+        let applied = app.world.resource::<Events<PurchaseApplied>>();
+        assert_eq!(applied.get_reader().read(applied).count(), 2);
+        assert_eq!(app.world.resource::<EventLog>().0.len(), 2);
+
+        app.world.send_event(Purchase::SellFinger { hand });
+        app.update();
+        assert_eq!(fingers(&mut app), 1);
+        assert_eq!(app.world.resource::<Score>().total_fingers, 1);
+    }
+
+    #[test]
+    fn bought_hands_are_numbered_past_the_highest_default_name() {
+        let mut app = crate::testing::purchase_app();
+        let locale = Locale::default();
+        let mut queue = bevy::ecs::system::CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &app.world);
+        spawn_hand(&mut commands, default_hand_name(&locale, 1), HandState::Filling, 0, 1);
+        spawn_hand(&mut commands, "Lefty".to_string(), HandState::Filling, 0, 1);
+        queue.apply(&mut app.world);
+        app.world.resource_mut::<Score>().stored_clicks = 1_000_000;
+        app.world.send_event(Purchase::Hand { count: 2 });
+        app.update();
+        let mut names: Vec<String> = app.world.query::<&HandName>().iter(&app.world).map(|name| name.0.clone()).collect();
+        names.sort();
+        assert_eq!(names, ["Hand 2", "Hand 3", "Hand 4", "Lefty"]);
+        assert_eq!(default_hand_number(&locale, "Hand 12"), Some(12));
+        assert_eq!(default_hand_number(&locale, "Lefty"), None);
+    }
+
+    #[test]
+    fn crossing_a_finger_tier_raises_the_multiplier_once() {
+        let mut app = crate::testing::test_app();
+        app.add_event::<MultiplierRaised>().add_systems(Update, multiplier_tier_system);
+        let raised = |app: &App| {
+            let events = app.world.resource::<Events<MultiplierRaised>>();
+            events.get_reader().read(events).map(|raised| raised.multiplier).collect::<Vec<_>>()
+        };
+        app.update();
+        let threshold = app.world.resource::<BalanceConfig>().multiplier_table[0];
+        app.world.resource_mut::<Score>().total_fingers = threshold - 1;
+        app.update();
+        assert!(raised(&app).is_empty());
+
+        app.world.resource_mut::<Score>().total_fingers = threshold;
+        app.update();
+        assert_eq!(raised(&app), vec![2]);
+
+        // a cashout's building doesn't count as a tier
+        app.world.resource_mut::<Events<MultiplierRaised>>().clear();
+        let mut score = app.world.resource_mut::<Score>();
+        score.buildings += 1;
+        score.total_fingers = 1;
+        app.update();
+        assert!(raised(&app).is_empty());
+    }
+
+    #[test]
+    fn trickle_keeps_fractions_until_they_make_a_click() {
+        let mut app = crate::testing::test_app();
+        app.init_resource::<Trickle>()
+            .init_resource::<FastForward>()
+            .insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(Duration::from_millis(250)))
+            .add_systems(Update, (trickle_system, collect_score_system.after(trickle_system)));
+        // 2 buildings at the default 0.5 a second, so a click a second
+        app.world.resource_mut::<Score>().buildings = 2;
+        for _ in 0..9 {
+            app.update();
+        }
+        let trickled = app.world.resource::<IncomeBreakdown>().total(ClickSource::Trickle);
+        assert_eq!(trickled, 2);
+        assert_eq!(app.world.resource::<Score>().stored_clicks, 2);
+        let fraction = app.world.resource::<Trickle>().fraction;
+        assert!((0.0..1.0).contains(&fraction));
+    }
+
+    #[test]
+    fn the_peak_click_rate_is_kept() {
+        let mut app = crate::testing::test_app();
+        app.init_resource::<ClickRate>()
+            .insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(Duration::from_millis(250)))
+            .add_systems(Update, click_rate_system);
+        app.update();
+        app.world.send_event(ClicksEmitted { amount: 1_000, source: ClickSource::Manual, hand: None });
+        let mut highest = 0.0f64;
+        // long enough for the clicks to leave the window and the rate to fall
+        for _ in 0..12 {
+            app.update();
+            highest = highest.max(app.world.resource::<ClickRate>().rate());
+        }
+        assert!(highest > 0.0);
+        assert!(app.world.resource::<ClickRate>().rate() < highest);
+        assert_eq!(app.world.resource::<Statistics>().peak_cps, highest);
+    }
+
+    #[test]
+    fn click_rates_include_the_influence_boost() {
+        let rate_after = |income_level| {
+            let mut app = crate::testing::test_app();
+            app.init_resource::<ClickRate>()
+                .insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(Duration::from_millis(250)))
+                .add_systems(Update, click_rate_system);
+            *app.world.resource_mut::<Score>().meta_upgrades.level_mut(MetaUpgrade::Income) = income_level;
+            app.update();
+            app.world.send_event(ClicksEmitted { amount: 1_000, source: ClickSource::Manual, hand: None });
+            app.update();
+            let manual = app.world.resource::<IncomeBreakdown>().rate(ClickSource::Manual);
+            let percent = app.world.resource::<Score>().meta_upgrades.income_percent();
+            (app.world.resource::<ClickRate>().rate(), manual, percent as f64 / 100.0)
+        };
+        let (plain, plain_manual, _) = rate_after(0);
+        let (boosted, boosted_manual, boost) = rate_after(1);
+        assert!(plain > 0.0 && boost > 1.0);
+        assert!((boosted / plain - boost).abs() < 1e-9);
+        assert!((boosted_manual / plain_manual - boost).abs() < 1e-9);
+    }
+
+    #[test]
+    fn upgraded_fingers_saturate_at_a_huge_multiplier() {
+        let mut app = crate::testing::test_app();
+        app.add_event::<ClickReadyFingers>()
+            .init_resource::<Combo>()
+            .insert_resource(Income { multiplier: u64::MAX, per_second: 0 })
+            .add_systems(Update, click_ready_fingers_system);
+        let mut ready = Timer::from_seconds(1.0, TimerMode::Once);
+        ready.tick(Duration::from_secs(1));
+        let finger = app.world.spawn(Clicker::with_per_click(2)).insert(TillCanClickTimer(ready)).id();
+        app.world.spawn((HandState::Filling, HandIncome::default())).push_children(&[finger]);
+        app.world.send_event(ClickReadyFingers);
+        app.update();
+
+        let events = app.world.resource::<Events<ClicksEmitted>>();
+        let amounts: Vec<u64> = events.get_reader().read(events).map(|clicks| clicks.amount).collect();
+        assert_eq!(amounts, vec![u64::MAX]);
+    }
+
+    #[test]
+    fn cached_income_matches_working_it_out_inline() {
+        let mut app = crate::testing::test_app();
+        app.init_resource::<Income>().add_systems(Update, compute_income_system);
+        app.world.resource_mut::<Score>().0 = Economy { total_fingers: 40, clap_speed: 1, ..Economy::default() };
+        let mut spawn = |state, per_clicks: &[u64]| {
+            let clickers: Vec<Entity> = per_clicks.iter().map(|per_click| app.world.spawn(Clicker::with_per_click(*per_click)).id()).collect();
+            app.world.spawn((state, HandIncome::default())).push_children(&clickers).id()
+        };
+        let filling = spawn(HandState::Filling, &[1, 2, 3]);
+        let combined = spawn(HandState::Combined, &[2, 2]);
+        let autoed = spawn(HandState::Autoed, &[5]);
+        app.update();
+
+        let score = app.world.resource::<Score>();
+        let balance = app.world.resource::<BalanceConfig>();
+        let multiplier = score.multiplier(balance);
+        // the way ui_system used to work it out, per hand and per frame
+        let inline = |per_click: u64, synergy, clapping| {
+            score.hand_rate(balance, with_synergy(per_click.saturating_mul(multiplier), synergy), clapping)
+        };
+        let expected = [
+            (filling, inline(6, balance.synergy(1), false)),
+            (combined, inline(4, balance.synergy(1), true)),
+            (autoed, inline(5, balance.synergy(0), true)),
+        ];
+        for (hand, per_second) in expected {
+            assert_eq!(app.world.get::<HandIncome>(hand).map(|income| income.per_second), Some(per_second));
+        }
+        let income = app.world.resource::<Income>();
+        assert_eq!(income.multiplier, multiplier);
+        assert_eq!(income.per_second, expected.iter().map(|(_, per_second)| per_second).sum::<u64>());
+        assert!(income.per_second > 0);
+    }
+}
@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+use bevy::{ecs::system::CommandQueue, prelude::*};
+
+use super::{
+    plugin::{auto_clap_system, collect_score_system, compute_income_system, trickle_system, update_timers_system},
+    Balance, Economy,
+};
+use crate::{
+    balance::BalanceConfig,
+    components::{spawn_hand, HandState},
+    resources::{FastForward, Income, Score, Trickle},
+    testing::manual_clock_app,
+};
+
+// a run to play out headless, for checking balance over hours of game time
+#[derive(Clone)]
+pub struct Simulation {
+    pub economy: Economy,
+    pub balance: Balance,
+    // each hand's state and fingers
+    pub hands: Vec<(HandState, u32)>,
+    // the virtual time every update advances by
+    pub step: Duration,
+}
+
+impl Simulation {
+    pub fn new(economy: Economy, hands: Vec<(HandState, u32)>) -> Self {
+        Simulation { economy, balance: Balance::default(), hands, step: Duration::from_secs(1) }
+    }
+}
+
+// nobody clicks and nothing gets bought: only autoed hands and the trickle
+// earn. every update is exactly `step` of virtual time, so the same
+// simulation always ends with the same score.
+pub fn simulate(simulation: &Simulation, duration: Duration) -> Score {
+    let mut app = manual_clock_app(simulation.step);
+    let per_click = simulation.economy.per_click();
+    app.insert_resource(Score(simulation.economy.clone()))
+        .insert_resource(BalanceConfig(simulation.balance.clone()))
+        .init_resource::<Income>()
+        .init_resource::<Trickle>()
+        .init_resource::<FastForward>()
+        .add_systems(Update, (
+            compute_income_system,
+            update_timers_system,
+            auto_clap_system,
+            trickle_system,
+            collect_score_system,
+        ).chain());
+    // an empty FixedUpdate would run dozens of times for each step otherwise
+    app.insert_resource(Time::<Fixed>::from_duration(simulation.step));
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, &app.world);
+    for (state, fingers) in &simulation.hands {
+        spawn_hand(&mut commands, String::new(), *state, *fingers, per_click);
+    }
+    queue.apply(&mut app.world);
+    let steps = duration.as_nanos().div_ceil(simulation.step.as_nanos().max(1));
+    for _ in 0..steps {
+        app.update();
+    }
+    app.world.remove_resource::<Score>().expect("the simulation's score")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::economy::WIN_SCORE;
+
+    #[test]
+    fn simulations_are_deterministic() {
+        let economy = Economy { total_fingers: 40, ..Economy::default() };
+        let simulation = Simulation::new(economy, vec![(HandState::Autoed, 20), (HandState::Combined, 20)]);
+        let hour = Duration::from_secs(60 * 60);
+        let score = simulate(&simulation, hour);
+        assert!(score.stored_clicks > 0);
+        assert_eq!(simulate(&simulation, hour).0, score.0);
+        // the step only changes how a clap's clicks land, not how many
+        let coarse = Simulation { step: Duration::from_secs(10), ..simulation };
+        assert_eq!(simulate(&coarse, hour).stored_clicks, score.stored_clicks);
+    }
+
+    #[test]
+    fn an_autoed_empire_wins_within_a_day() {
+        // ten autoed hands of 50 fingers, and nobody buying anything more
+        let economy = Economy { total_fingers: 500, hands_owned: 10, ..Economy::default() };
+        let simulation = Simulation {
+            step: Duration::from_secs(60),
+            ..Simulation::new(economy, vec![(HandState::Autoed, 50); 10])
+        };
+        let hours = |hours: u64| simulate(&simulation, Duration::from_secs(hours * 60 * 60)).stored_clicks;
+        assert!(hours(1) < WIN_SCORE);
+        assert!(hours(24) >= WIN_SCORE);
+    }
+}
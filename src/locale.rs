@@ -0,0 +1,143 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use thousands::Separable;
+
+// the tables are compiled in so a missing or broken file can't leave the ui blank
+const ENGLISH_TABLE : &str = include_str!("../assets/locale/en.ron");
+const SPANISH_TABLE : &str = include_str!("../assets/locale/es.ron");
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    Spanish,
+}
+
+impl Language {
+    pub const ALL : [Language; 2] = [Language::English, Language::Spanish];
+
+    // shown in the selector as-is, so a player can find their own language
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Spanish => "Español",
+        }
+    }
+
+    fn table(self) -> &'static str {
+        match self {
+            Language::English => ENGLISH_TABLE,
+            Language::Spanish => SPANISH_TABLE,
+        }
+    }
+
+    pub fn group_digits(self, n: u64) -> String {
+        match self {
+            Language::English => n.separate_with_commas(),
+            Language::Spanish => n.separate_with_dots(),
+        }
+    }
+
+    pub fn decimal_point(self) -> char {
+        match self {
+            Language::English => '.',
+            Language::Spanish => ',',
+        }
+    }
+
+    pub fn decimal(self, value: f64, places: usize) -> String {
+        format!("{:.*}", places, value).replace('.', &self.decimal_point().to_string())
+    }
+}
+
+fn parse_table(language: Language) -> BTreeMap<String, String> {
+    ron::from_str(language.table()).unwrap_or_else(|err| {
+        error!("unreadable {:?} strings: {}", language, err);
+        BTreeMap::new()
+    })
+}
+
+// ui strings for the language picked in settings
+#[derive(Resource)]
+pub struct Locale {
+    pub language: Language,
+    strings: BTreeMap<String, String>,
+    // english, for keys the current language hasn't translated yet
+    fallback: BTreeMap<String, String>,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale::new(Language::default())
+    }
+}
+
+impl Locale {
+    pub fn new(language: Language) -> Self {
+        let fallback = parse_table(Language::English);
+        let strings = match language {
+            Language::English => fallback.clone(),
+            _ => parse_table(language),
+        };
+        Locale { language, strings, fallback }
+    }
+
+    // a key nobody translated shows up as itself, which is easy to spot
+    pub fn t(&self, key: &str) -> String {
+        self.strings.get(key)
+            .or_else(|| self.fallback.get(key))
+            .cloned()
+            .unwrap_or_else(|| key.to_string())
+    }
+
+    // fills `{name}` placeholders
+    pub fn tf(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        args.iter().fold(self.t(key), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), &value.to_string())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholders(text: &str) -> Vec<&str> {
+        let mut found: Vec<&str> = text.split('{').skip(1).filter_map(|rest| rest.split_once('}')).map(|(name, _)| name).collect();
+        found.sort_unstable();
+        found
+    }
+
+    #[test]
+    fn every_language_translates_every_key() {
+        let english = parse_table(Language::English);
+        assert!(!english.is_empty());
+        for language in Language::ALL {
+            let table = parse_table(language);
+            for (key, text) in &english {
+                let translated = table.get(key).unwrap_or_else(|| panic!("{:?} is missing {}", language, key));
+                assert_eq!(placeholders(translated), placeholders(text), "{:?} {}", language, key);
+            }
+            for key in table.keys() {
+                assert!(english.contains_key(key), "{:?} has unknown key {}", language, key);
+            }
+        }
+    }
+
+    #[test]
+    fn lookups_fill_placeholders_and_fall_back() {
+        let locale = Locale::new(Language::Spanish);
+        assert_eq!(locale.tf("store.clicks", &[("clicks", &"1.234")]), "Clics: 1.234");
+        assert_eq!(locale.t("no.such.key"), "no.such.key");
+        assert_eq!(Locale::default().tf("hand.default_name", &[("number", &3)]), "Hand 3");
+    }
+
+    #[test]
+    fn digits_group_per_language() {
+        assert_eq!(Language::English.group_digits(1234567), "1,234,567");
+        assert_eq!(Language::Spanish.group_digits(1234567), "1.234.567");
+        assert_eq!(Language::Spanish.decimal(12.345, 1), "12,3");
+    }
+}
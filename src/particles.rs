@@ -0,0 +1,768 @@
+use bevy::{
+    diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    ecs::system::SystemParam,
+    prelude::*,
+    render::render_resource::TextureFormat,
+    utils::HashSet,
+};
+use bevy_particle_systems::{*, VelocityModifier::*};
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    resources::{ClicksEmitted, GameRng, LowPower, Settings},
+    states::State,
+};
+
+pub struct ParticlesPlugin;
+
+impl Plugin for ParticlesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SparksRequested>()
+            .add_event::<CursorBurstRequested>()
+            .init_resource::<AutoQuality>()
+            .add_systems(Update, (
+                burst_system,
+                cursor_burst_system,
+                burst_deactivator_system,
+                cull_offscreen_emitters_system,
+                auto_quality_system,
+            ).run_if(in_state(State::Game)));
+    }
+}
+
+#[derive(Component)]
+pub struct BurstTimer(Timer);
+
+impl Default for BurstTimer {
+    fn default() -> Self {
+        BurstTimer(Timer::from_seconds(0.1, TimerMode::Once))
+    }
+}
+
+// a one-off burst at a point in the world, like where a building was clicked
+#[derive(Event)]
+pub struct SparksRequested {
+    pub position: Vec3,
+    pub theme: ParticleTheme,
+}
+
+// every batch of clicks lights up some of the skyline
+pub fn burst_system(
+    mut clicker_events: EventReader<ClicksEmitted>,
+    mut spark_events: EventReader<SparksRequested>,
+    mut particle_pool: ParticlePool,
+    mut commands: Commands,
+    low_power: Res<LowPower>,
+) {
+    if low_power.0 {
+        spark_events.clear();
+        clicker_events.clear();
+        return;
+    }
+    // Playing is inserted by deferred commands, so the pool still sees the
+    // emitters started this frame as idle until we skip them
+    let mut started = HashSet::new();
+    for SparksRequested { position, theme } in spark_events.read() {
+        particle_pool.burst_at(&mut commands, &mut started, *position, *theme);
+    }
+    for ClicksEmitted { amount, .. } in clicker_events.read() {
+        particle_pool.burst(&mut commands, &mut started, *amount);
+    }
+}
+
+// a manual click on a finger, at the pointer in logical window pixels
+#[derive(Event)]
+pub struct CursorBurstRequested {
+    pub at: Vec2,
+}
+
+#[derive(Component)]
+pub struct CursorEmitter;
+
+pub const CURSOR_THEME : ParticleTheme = ParticleTheme { start: Color::WHITE, middle: Color::GOLD };
+// sparks in one cursor burst at full density and quality
+pub const CURSOR_BURST_PARTICLES : f32 = 40.0;
+
+// a single puff of sparks that despawns itself once they've all faded
+pub fn cursor_particle_system(texture: Handle<Image>, density: f32, quality: f32) -> ParticleSystem {
+    let count = (CURSOR_BURST_PARTICLES * density * quality.clamp(0.1, 1.0)).ceil() as usize;
+    ParticleSystem {
+        max_particles: count,
+        texture: texture.into(),
+        spawn_rate_per_second: 0.0.into(),
+        bursts: vec![ParticleBurst::new(0.0, count)],
+        initial_speed: JitteredValue::jittered(150.0, -75.0..75.0),
+        velocity_modifiers: vec![Drag(0.02.into())],
+        lifetime: JitteredValue::jittered(0.4, -0.15..0.15),
+        color: CURSOR_THEME.gradient(),
+        looping: false,
+        system_duration_seconds: 0.1,
+        scale: 0.3.into(),
+        despawn_on_finish: true,
+        ..ParticleSystem::default()
+    }
+}
+
+// separate from the pool, which only places emitters on buildings
+pub fn cursor_burst_system(
+    mut requests: EventReader<CursorBurstRequested>,
+    mut commands: Commands,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    settings: Res<Settings>,
+    auto_quality: Res<AutoQuality>,
+    low_power: Res<LowPower>,
+    asset_server: Res<AssetServer>,
+) {
+    let Ok((camera, camera_transform)) = cameras.get_single() else {
+        requests.clear();
+        return;
+    };
+    if low_power.0 {
+        requests.clear();
+        return;
+    }
+    for CursorBurstRequested { at } in requests.read() {
+        let Some(position) = camera.viewport_to_world_2d(camera_transform, *at) else {
+            continue;
+        };
+        let mut particle_system = cursor_particle_system(
+            asset_server.load("spark.png"),
+            settings.particle_density,
+            particle_quality(&settings, &auto_quality),
+        );
+        if settings.reduce_motion {
+            calm(&mut particle_system, CURSOR_THEME);
+        }
+        commands.spawn((
+            CursorEmitter,
+            ParticleSystemBundle {
+                particle_system,
+                transform: Transform::from_translation(position.extend(100.0)),
+                ..ParticleSystemBundle::default()
+            },
+            Playing,
+        ));
+    }
+}
+
+pub fn burst_deactivator_system(
+    mut commands: Commands,
+    mut burst_timers: Query<(Entity, &mut BurstTimer), With<Playing>>,
+    time: Res<Time>,
+) {
+    for (entity, mut burst_timer) in burst_timers.iter_mut() {
+        if burst_timer.0.tick(time.delta()).just_finished() {
+            commands.entity(entity).remove::<Playing>();
+        }
+    }
+    
+}
+
+// auto quality backs off below this frame rate
+pub const AUTO_QUALITY_TARGET_FPS : f64 = 55.0;
+// and only comes back above this one, so it doesn't flip every second
+pub const AUTO_QUALITY_RESTORE_FPS : f64 = 59.0;
+pub const AUTO_QUALITY_STEP_SECS : f32 = 1.0;
+pub const AUTO_QUALITY_MIN : f32 = 0.1;
+
+// the quality auto mode settled on. drops quickly, recovers slowly.
+#[derive(Resource)]
+pub struct AutoQuality {
+    pub factor: f32,
+    step: Timer,
+}
+
+impl Default for AutoQuality {
+    fn default() -> Self {
+        AutoQuality {
+            factor: 1.0,
+            step: Timer::from_seconds(AUTO_QUALITY_STEP_SECS, TimerMode::Repeating),
+        }
+    }
+}
+
+impl AutoQuality {
+    pub fn adjust(&mut self, fps: f64) {
+        if fps < AUTO_QUALITY_TARGET_FPS {
+            self.factor = (self.factor * 0.8).max(AUTO_QUALITY_MIN);
+        } else if fps >= AUTO_QUALITY_RESTORE_FPS {
+            self.factor = (self.factor + 0.1).min(1.0);
+        }
+    }
+}
+
+// the quality new bursts are played at
+pub fn particle_quality(settings: &Settings, auto: &AutoQuality) -> f32 {
+    if settings.auto_quality { auto.factor } else { settings.quality }
+}
+
+pub fn auto_quality_system(
+    settings: Res<Settings>,
+    diagnostics: Res<DiagnosticsStore>,
+    mut auto: ResMut<AutoQuality>,
+    time: Res<Time>,
+) {
+    if !settings.auto_quality || !auto.step.tick(time.delta()).just_finished() {
+        return;
+    }
+    if let Some(fps) = diagnostics.get(FrameTimeDiagnosticsPlugin::FPS).and_then(|fps| fps.average()) {
+        auto.adjust(fps);
+    }
+}
+
+// how far outside the view an emitter still counts as on screen, enough to
+// cover half a building sprite and the sparks it throws
+pub const CULL_MARGIN : f32 = 600.0;
+
+// the world-space rect the camera sees, grown by the cull margin
+pub fn view_rect(camera: &Transform, projection: &OrthographicProjection) -> Rect {
+    let center = camera.translation.truncate();
+    let margin = Vec2::splat(CULL_MARGIN);
+    Rect::from_corners(projection.area.min + center - margin, projection.area.max + center + margin)
+}
+
+pub type CameraView = (&'static Transform, &'static OrthographicProjection);
+pub type PlayingEmitter = (With<PooledEmitter>, With<Playing>);
+
+// stops emitters the camera has panned or zoomed away from. they go back to
+// the pool and the next burst places them somewhere visible.
+pub fn cull_offscreen_emitters_system(
+    mut commands: Commands,
+    cameras: Query<CameraView, (With<Camera2d>, Without<PooledEmitter>)>,
+    playing: Query<(Entity, &Transform), PlayingEmitter>,
+) {
+    let Ok((camera, projection)) = cameras.get_single() else {
+        return;
+    };
+    let view = view_rect(camera, projection);
+    for (entity, transform) in &playing {
+        if !view.contains(transform.translation.truncate()) {
+            commands.entity(entity).remove::<Playing>();
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ReadableImageError {
+    UnsupportedFormat(TextureFormat),
+    Truncated { expected: usize, actual: usize },
+}
+
+pub struct ReadableImage<'a> {
+    image: &'a Image,
+    pixel_stride: usize,
+    row_stride: usize,
+}
+
+impl ReadableImage<'_> {
+    pub fn new(image: &Image) -> Result<ReadableImage<'_>, ReadableImageError> {
+        let format = image.texture_descriptor.format;
+        // only uncompressed, single-aspect formats can be read pixel by pixel
+        let pixel_stride = match (format.block_dimensions(), format.block_size(None)) {
+            ((1, 1), Some(size)) => size as usize,
+            _ => return Err(ReadableImageError::UnsupportedFormat(format)),
+        };
+        // CPU-side image data is tightly packed; padding only exists on the GPU copy
+        let row_stride = image.size().x as usize * pixel_stride;
+        let expected = row_stride * image.size().y as usize;
+        if image.data.len() < expected {
+            return Err(ReadableImageError::Truncated { expected, actual: image.data.len() });
+        }
+
+        Ok(ReadableImage {
+            image,
+            pixel_stride,
+            row_stride,
+        })
+    }
+
+    pub fn with_nonzero<T : FnMut(f32, f32, &[u8])>(&self, rect: Rect, mut f: T) {
+        let image_rect = Rect { min: Vec2::ZERO, max: self.image.size().as_vec2() };
+        let rect = image_rect.intersect(rect);
+        let minx = rect.min.x as usize;
+        let maxx = rect.max.x as usize;
+        let miny = rect.min.y as usize;
+        let maxy = rect.max.y as usize;
+        
+        // output center
+        let center = Vec2::new((maxx - minx) as f32 / 2.0, (maxy - miny) as f32 / 2.0);
+
+
+        for y in miny..maxy {
+            for x in minx..maxx {
+                let offset = (y * self.row_stride) + (x * self.pixel_stride);
+                let pixel = &self.image.data[offset..offset + self.pixel_stride];
+                
+                if pixel.iter().any(|&x| x != 0) {
+                    let x = (x - minx) as f32;
+                    let y = (y - miny) as f32;
+
+                    // invert and center y
+                    let y = (rect.height() - y) - center.y;
+
+                    // center x
+                    let x = x - center.x;
+                    f(x, y, pixel);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum EmitterKind {
+    FacingCamera,
+    FacingLeft,
+}
+
+// how a building's sparks look. later buildings get their own colors so a
+// growing skyline shows how far the run has come.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParticleTheme {
+    pub start: Color,
+    pub middle: Color,
+}
+
+pub const PARTICLE_THEMES : [ParticleTheme; 5] = [
+    ParticleTheme { start: Color::RED, middle: Color::YELLOW },
+    ParticleTheme { start: Color::BLUE, middle: Color::CYAN },
+    ParticleTheme { start: Color::GREEN, middle: Color::GOLD },
+    ParticleTheme { start: Color::PURPLE, middle: Color::PINK },
+    ParticleTheme { start: Color::ORANGE_RED, middle: Color::WHITE },
+];
+
+impl ParticleTheme {
+    // the table repeats once every theme has been used
+    pub fn for_building(index: usize) -> ParticleTheme {
+        PARTICLE_THEMES[index % PARTICLE_THEMES.len()]
+    }
+
+    fn gradient(&self) -> ColorOverTime {
+        ColorOverTime::Gradient(Curve::new(vec![
+            CurvePoint::new(self.start, 0.0),
+            CurvePoint::new(self.middle, 0.75),
+            CurvePoint::new(Color::rgba(1.0, 1.0, 1.0, 0.0), 1.0),
+        ]))
+    }
+
+    // a single color fading out, with no swing between two hues
+    fn steady_gradient(&self) -> ColorOverTime {
+        let [r, g, b, _] = self.middle.as_rgba_f32();
+        ColorOverTime::Gradient(Curve::new(vec![
+            CurvePoint::new(Color::rgba(r, g, b, 0.6), 0.0),
+            CurvePoint::new(Color::rgba(r, g, b, 0.0), 1.0),
+        ]))
+    }
+}
+
+// local offsets of the lit pixels on a building sprite, where pooled emitters
+// get placed, and the colors they burst in
+#[derive(Component)]
+pub struct EmitterSites {
+    pub sites: Vec<(Vec3, EmitterKind)>,
+    pub theme: ParticleTheme,
+}
+
+// upper bound on live emitters, no matter how many buildings there are.
+// raise it for denser bursts, lower it for slower machines.
+pub const PARTICLE_POOL_SIZE : usize = 64;
+
+#[derive(Component)]
+pub struct PooledEmitter;
+
+// a burst grows this much with every power of ten of clicks behind it
+pub const BURST_GROWTH_PER_DECADE : f32 = 0.25;
+// and stops growing here. even this big, a burst is a small part of
+// max_particles, so the cap never cuts one short.
+pub const MAX_BURST_INTENSITY : f32 = 3.0;
+
+// 1 for a single click, up to MAX_BURST_INTENSITY for a huge payout
+pub fn burst_intensity(clicks: u64) -> f32 {
+    let decades = (clicks.max(1) as f64).log10() as f32;
+    (1.0 + decades * BURST_GROWTH_PER_DECADE).min(MAX_BURST_INTENSITY)
+}
+
+// intensity scales how many particles spawn, and how fast and long they
+// fly. max_particles only follows quality.
+pub fn emitter_particle_system(
+    kind: EmitterKind,
+    theme: ParticleTheme,
+    texture: Handle<Image>,
+    density: f32,
+    quality: f32,
+    intensity: f32,
+) -> ParticleSystem {
+    let quality = quality.clamp(0.1, 1.0);
+    let intensity = intensity.clamp(1.0, MAX_BURST_INTENSITY);
+    let max_particles = (10_000.0 * quality) as usize;
+    let spawn_rate = 1000.0 * density * quality * intensity;
+    // speed and lifetime both stretch the burst's reach, so each takes half
+    let stretch = intensity.sqrt();
+    match kind {
+        EmitterKind::FacingCamera => ParticleSystem {
+            max_particles,
+            texture: texture.into(),
+            spawn_rate_per_second: spawn_rate.into(),
+            initial_speed: JitteredValue::jittered(20.0 * stretch, -500.0 * stretch..500.0 * stretch),
+            velocity_modifiers: vec![Drag(0.001.into()), Vector(VectorOverTime::Constant(Vec3::new(0.0, -10.0, 0.0)))],
+            lifetime: JitteredValue::jittered(0.1 * stretch, 0.1 * stretch..0.5 * stretch),
+            color: theme.gradient(),
+            looping: true,
+            system_duration_seconds: 10.0,
+            max_distance: Some(600.0),
+            initial_scale: 0.01.into(),
+            scale: 50.0.into(),
+            ..ParticleSystem::default()
+        },
+        EmitterKind::FacingLeft => ParticleSystem {
+            max_particles,
+            emitter_shape: EmitterShape::CircleSegment(CircleSegment {
+                opening_angle: 0.5 * std::f32::consts::PI,
+                radius: 0.0.into(),
+                direction_angle: std::f32::consts::PI,
+            }),
+            texture: texture.into(),
+            spawn_rate_per_second: spawn_rate.into(),
+            initial_speed: JitteredValue::jittered(200.0 * stretch, -50.0 * stretch..50.0 * stretch),
+            velocity_modifiers: vec![Drag(0.01.into())],
+            lifetime: JitteredValue::jittered(1.0 * stretch, -0.5 * stretch..0.5 * stretch),
+            color: theme.gradient(),
+            looping: true,
+            system_duration_seconds: 10.0,
+            max_distance: Some(300.0),
+            scale: 0.5.into(),
+            ..ParticleSystem::default()
+        },
+    }
+}
+
+// the most sparks a second one emitter spawns with reduce motion on
+pub const CALM_SPAWN_RATE : f32 = 250.0;
+
+// reduce motion: fewer sparks, in one steady color instead of flickering
+// from one hue to the other
+pub fn calm(system: &mut ParticleSystem, theme: ParticleTheme) {
+    let rate = system.spawn_rate_per_second.at_lifetime_pct(0.0).min(CALM_SPAWN_RATE);
+    system.spawn_rate_per_second = rate.into();
+    system.color = theme.steady_gradient();
+}
+
+pub fn spawn_particle_pool(commands: &mut Commands) {
+    for _ in 0..PARTICLE_POOL_SIZE {
+        commands.spawn((PooledEmitter, BurstTimer::default(), ParticleSystemBundle::default()));
+    }
+}
+
+pub type IdleEmitter = (Entity, &'static mut BurstTimer, &'static mut ParticleSystem, &'static mut Transform);
+
+#[derive(SystemParam)]
+pub struct ParticlePool<'w, 's> {
+    idle: Query<'w, 's, IdleEmitter, (With<PooledEmitter>, Without<Playing>)>,
+    playing: Query<'w, 's, (), PlayingEmitter>,
+    sites: Query<'w, 's, (&'static Transform, &'static EmitterSites), Without<PooledEmitter>>,
+    cameras: Query<'w, 's, CameraView, (With<Camera2d>, Without<PooledEmitter>)>,
+    rng: ResMut<'w, GameRng>,
+    settings: Res<'w, Settings>,
+    auto_quality: Res<'w, AutoQuality>,
+    asset_server: Res<'w, AssetServer>,
+}
+
+impl ParticlePool<'_, '_> {
+    // moves up to `count` idle emitters onto random lit pixels and starts them.
+    // the work is bounded by the pool, however many clicks came in. buildings
+    // out of view are skipped, without a camera every building counts.
+    fn burst(&mut self, commands: &mut Commands, started: &mut HashSet<Entity>, count: u64) {
+        let view = self.cameras.get_single().ok().map(|(camera, projection)| view_rect(camera, projection));
+        let mut buildings: Vec<_> = self.sites
+            .iter()
+            .filter(|(_, sites)| !sites.sites.is_empty())
+            .filter(|(building, _)| view.is_none_or(|view| view.contains(building.translation.truncate())))
+            .collect();
+        if buildings.is_empty() {
+            return;
+        }
+        let texture = self.asset_server.load("spark.png");
+        // lower quality lets fewer of the pool's emitters run at once
+        let quality = particle_quality(&self.settings, &self.auto_quality);
+        let budget = ((PARTICLE_POOL_SIZE as f32 * quality).ceil() as usize)
+            .saturating_sub(self.playing.iter().count() + started.len());
+        let mut bursts = budget.min(PARTICLE_POOL_SIZE).min(usize::try_from(count).unwrap_or(usize::MAX));
+        // simple particles: at most one emitter per building, on a random few
+        let simple = self.settings.simple_particles;
+        if simple {
+            bursts = bursts.min(buildings.len());
+            buildings.shuffle(&mut self.rng.rng);
+        }
+        let stride = self.settings.emitter_stride.max(1) as usize;
+        let intensity = if self.settings.scaled_bursts && !self.settings.reduce_motion { burst_intensity(count) } else { 1.0 };
+        let mut claimed = Vec::with_capacity(bursts);
+        let idle = self.idle.iter_mut().filter(|(entity, ..)| !started.contains(entity));
+        for (n, (entity, mut timer, mut particle_system, mut transform)) in idle.take(bursts).enumerate() {
+            let (building, sites) = if simple { buildings[n] } else { buildings[self.rng.rng.gen_range(0..buildings.len())] };
+            let site = self.rng.rng.gen_range(0..sites.sites.len().div_ceil(stride)) * stride;
+            let (offset, kind) = sites.sites[site];
+            *transform = building.mul_transform(Transform::from_translation(offset));
+            *particle_system = emitter_particle_system(kind, sites.theme, texture.clone(), self.settings.particle_density, quality, intensity);
+            if self.settings.reduce_motion {
+                calm(&mut particle_system, sites.theme);
+            }
+            commands.entity(entity).insert(Playing);
+            claimed.push(entity);
+            timer.0.reset();
+        }
+        started.extend(claimed);
+    }
+
+    // starts a single emitter at `position`, if one is free
+    fn burst_at(&mut self, commands: &mut Commands, started: &mut HashSet<Entity>, position: Vec3, theme: ParticleTheme) {
+        let free = self.idle.iter_mut().find(|(entity, ..)| !started.contains(entity));
+        let Some((entity, mut timer, mut particle_system, mut transform)) = free else {
+            return;
+        };
+        let quality = particle_quality(&self.settings, &self.auto_quality);
+        *transform = Transform::from_translation(position);
+        *particle_system = emitter_particle_system(
+            EmitterKind::FacingCamera,
+            theme,
+            self.asset_server.load("spark.png"),
+            self.settings.particle_density,
+            quality,
+            1.0,
+        );
+        if self.settings.reduce_motion {
+            calm(&mut particle_system, theme);
+        }
+        commands.entity(entity).insert(Playing);
+        started.insert(entity);
+        timer.0.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{economy::collect_score_system, resources::{ClickSource, Score}, testing::test_app};
+
+    fn synthetic_image(width: u32, height: u32, data: Vec<u8>, format: TextureFormat) -> Image {
+        use bevy::render::render_resource::{Extent3d, TextureDimension};
+        Image::new(Extent3d { width, height, depth_or_array_layers: 1 }, TextureDimension::D2, data, format)
+    }
+
+    fn visited(image: &Image, rect: Rect) -> Vec<(f32, f32, Vec<u8>)> {
+        let mut visited = Vec::new();
+        ReadableImage::new(image).unwrap().with_nonzero(rect, |x, y, pixel| visited.push((x, y, pixel.to_vec())));
+        visited
+    }
+
+    #[test]
+    fn readable_image_rgba8_visits_lit_pixels() {
+        let mut data = vec![0u8; 2 * 2 * 4];
+        // pixel (1, 0)
+        data[4..8].copy_from_slice(&[255, 0, 0, 255]);
+        let image = synthetic_image(2, 2, data, TextureFormat::Rgba8UnormSrgb);
+        let full = Rect::new(0.0, 0.0, 2.0, 2.0);
+        assert_eq!(visited(&image, full), vec![(0.0, 1.0, vec![255, 0, 0, 255])]);
+    }
+
+    #[test]
+    fn readable_image_single_channel_and_wide_formats() {
+        // 3x1, middle pixel lit
+        let image = synthetic_image(3, 1, vec![0, 7, 0], TextureFormat::R8Unorm);
+        assert_eq!(visited(&image, Rect::new(0.0, 0.0, 3.0, 1.0)), vec![(-0.5, 0.5, vec![7])]);
+
+        // 2x2 at 8 bytes per pixel, bottom right lit
+        let mut data = vec![0u8; 2 * 2 * 8];
+        data[24..32].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        let image = synthetic_image(2, 2, data, TextureFormat::Rgba16Uint);
+        let lit = visited(&image, Rect::new(0.0, 0.0, 2.0, 2.0));
+        assert_eq!(lit, vec![(0.0, 0.0, vec![1, 2, 3, 4, 5, 6, 7, 8])]);
+
+        // a sub-rect only reports pixels inside it
+        assert!(visited(&image, Rect::new(0.0, 0.0, 1.0, 1.0)).is_empty());
+    }
+
+    #[test]
+    fn readable_image_rejects_unsupported_formats() {
+        let mut image = Image::default();
+        image.texture_descriptor.format = TextureFormat::Bc1RgbaUnorm;
+        assert_eq!(
+            ReadableImage::new(&image).err(),
+            Some(ReadableImageError::UnsupportedFormat(TextureFormat::Bc1RgbaUnorm))
+        );
+
+        let mut image = synthetic_image(2, 2, vec![0; 16], TextureFormat::Rgba8Unorm);
+        image.data.truncate(10);
+        assert_eq!(ReadableImage::new(&image).err(), Some(ReadableImageError::Truncated { expected: 16, actual: 10 }));
+    }
+
+    #[test]
+    fn quality_scales_emitters() {
+        let theme = ParticleTheme::for_building(0);
+        let full = emitter_particle_system(EmitterKind::FacingCamera, theme, Handle::default(), 1.0, 1.0, 1.0);
+        let low = emitter_particle_system(EmitterKind::FacingLeft, theme, Handle::default(), 1.0, 0.1, 1.0);
+        assert_eq!(full.max_particles, 10_000);
+        assert_eq!(low.max_particles, 1_000);
+        // out of range quality is clamped rather than turning particles off
+        assert_eq!(emitter_particle_system(EmitterKind::FacingLeft, theme, Handle::default(), 1.0, 0.0, 1.0).max_particles, 1_000);
+    }
+
+    #[test]
+    fn bursts_grow_with_clicks_within_the_budget() {
+        assert_eq!(burst_intensity(0), 1.0);
+        assert_eq!(burst_intensity(1), 1.0);
+        assert!(burst_intensity(1_000) > burst_intensity(10));
+        assert_eq!(burst_intensity(u64::MAX), MAX_BURST_INTENSITY);
+
+        let theme = ParticleTheme::for_building(0);
+        let small = emitter_particle_system(EmitterKind::FacingLeft, theme, Handle::default(), 1.0, 1.0, burst_intensity(1));
+        let big = emitter_particle_system(EmitterKind::FacingLeft, theme, Handle::default(), 1.0, 1.0, burst_intensity(u64::MAX));
+        assert!(big.spawn_rate_per_second.at_lifetime_pct(0.0) > small.spawn_rate_per_second.at_lifetime_pct(0.0));
+        assert!(big.initial_speed.value > small.initial_speed.value);
+        assert!(big.lifetime.value > small.lifetime.value);
+        assert_eq!(big.max_particles, small.max_particles);
+        // a whole burst timer at the biggest intensity fits under the cap
+        let burst_secs = BurstTimer::default().0.duration().as_secs_f32();
+        let per_burst = big.spawn_rate_per_second.at_lifetime_pct(0.0) * burst_secs;
+        assert!((per_burst as usize) < big.max_particles);
+    }
+
+    #[test]
+    fn calm_bursts_are_capped() {
+        let theme = ParticleTheme::for_building(0);
+        let mut dense = emitter_particle_system(EmitterKind::FacingCamera, theme, Handle::default(), 2.0, 1.0, MAX_BURST_INTENSITY);
+        calm(&mut dense, theme);
+        assert_eq!(dense.spawn_rate_per_second.at_lifetime_pct(0.0), CALM_SPAWN_RATE);
+        // already sparse enough, left alone
+        let mut sparse = emitter_particle_system(EmitterKind::FacingLeft, theme, Handle::default(), 0.1, 0.1, 1.0);
+        calm(&mut sparse, theme);
+        assert_eq!(sparse.spawn_rate_per_second.at_lifetime_pct(0.0), 10.0);
+    }
+
+    #[test]
+    fn cursor_bursts_are_one_shot() {
+        let system = cursor_particle_system(Handle::default(), 1.0, 1.0);
+        assert!(!system.looping && system.despawn_on_finish);
+        assert_eq!(system.bursts.iter().map(|burst| burst.count).sum::<usize>(), CURSOR_BURST_PARTICLES as usize);
+        assert!(system.bursts.iter().all(|burst| burst.time < system.system_duration_seconds));
+        // never empty, however low the quality
+        assert!(cursor_particle_system(Handle::default(), 0.1, 0.0).max_particles >= 1);
+    }
+
+    #[test]
+    fn auto_quality_backs_off_and_recovers_with_hysteresis() {
+        let mut auto = AutoQuality::default();
+        auto.adjust(30.0);
+        auto.adjust(30.0);
+        let lowered = auto.factor;
+        assert!(lowered < 1.0);
+        // between the thresholds nothing moves
+        auto.adjust(57.0);
+        assert_eq!(auto.factor, lowered);
+        for _ in 0..100 {
+            auto.adjust(10.0);
+        }
+        assert_eq!(auto.factor, AUTO_QUALITY_MIN);
+        for _ in 0..100 {
+            auto.adjust(60.0);
+        }
+        assert_eq!(auto.factor, 1.0);
+
+        let mut settings = Settings { quality: 0.3, ..Settings::default() };
+        assert_eq!(particle_quality(&settings, &auto), 0.3);
+        settings.auto_quality = true;
+        assert_eq!(particle_quality(&settings, &auto), 1.0);
+    }
+
+    #[test]
+    fn view_rect_follows_the_camera() {
+        let projection = OrthographicProjection {
+            area: Rect::new(-640.0, -360.0, 640.0, 360.0),
+            ..default()
+        };
+        let view = view_rect(&Transform::from_xyz(-2000.0, 0.0, 0.0), &projection);
+        assert!(view.contains(Vec2::new(-2000.0, 0.0)));
+        assert!(view.contains(Vec2::new(-2640.0 - CULL_MARGIN, 0.0)));
+        assert!(!view.contains(Vec2::ZERO));
+    }
+
+    #[test]
+    fn buildings_cycle_through_themes() {
+        assert_eq!(ParticleTheme::for_building(0), ParticleTheme { start: Color::RED, middle: Color::YELLOW });
+        assert_ne!(ParticleTheme::for_building(1), ParticleTheme::for_building(0));
+        assert_eq!(ParticleTheme::for_building(PARTICLE_THEMES.len() + 2), ParticleTheme::for_building(2));
+    }
+
+    #[test]
+    fn a_billion_clicks_burst_in_bounded_time() {
+        let mut app = test_app();
+        app.add_event::<SparksRequested>()
+            .init_resource::<AutoQuality>()
+            .add_systems(Startup, |mut commands: Commands| spawn_particle_pool(&mut commands))
+            .add_systems(Update, (collect_score_system, burst_system));
+        app.world.spawn((Transform::default(), EmitterSites {
+            sites: vec![(Vec3::ZERO, EmitterKind::FacingCamera)],
+            theme: ParticleTheme::for_building(0),
+        }));
+        app.world.send_event(ClicksEmitted { amount: 1_000_000_000, source: ClickSource::Manual, hand: None });
+
+        let started = std::time::Instant::now();
+        app.update();
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        assert_eq!(app.world.resource::<Score>().stored_clicks, 1_000_000_000);
+        let playing = app.world
+            .query_filtered::<(), (With<PooledEmitter>, With<Playing>)>()
+            .iter(&app.world)
+            .count();
+        assert_eq!(playing, PARTICLE_POOL_SIZE);
+    }
+
+    #[test]
+    fn simple_particles_burst_once_per_building() {
+        let mut app = test_app();
+        app.add_event::<SparksRequested>()
+            .init_resource::<AutoQuality>()
+            .add_systems(Startup, |mut commands: Commands| spawn_particle_pool(&mut commands))
+            .add_systems(Update, (collect_score_system, burst_system));
+        app.world.resource_mut::<Settings>().simple_particles = true;
+        for (index, x) in [0.0, 500.0].into_iter().enumerate() {
+            app.world.spawn((Transform::from_xyz(x, 0.0, 0.0), EmitterSites {
+                sites: vec![(Vec3::ZERO, EmitterKind::FacingCamera)],
+                theme: ParticleTheme::for_building(index),
+            }));
+        }
+        app.world.send_event(ClicksEmitted { amount: 1_000_000, source: ClickSource::Manual, hand: None });
+        app.update();
+
+        let mut placed: Vec<f32> = app.world
+            .query_filtered::<&Transform, (With<PooledEmitter>, With<Playing>)>()
+            .iter(&app.world)
+            .map(|transform| transform.translation.x)
+            .collect();
+        placed.sort_by(f32::total_cmp);
+        assert_eq!(placed, vec![0.0, 500.0]);
+    }
+
+    #[test]
+    fn sparks_requested_together_start_their_own_emitters() {
+        let mut app = test_app();
+        app.add_event::<SparksRequested>()
+            .init_resource::<AutoQuality>()
+            .add_systems(Startup, |mut commands: Commands| spawn_particle_pool(&mut commands))
+            .add_systems(Update, burst_system);
+        let theme = ParticleTheme::for_building(0);
+        app.world.send_event(SparksRequested { position: Vec3::new(-100.0, 0.0, 1.0), theme });
+        app.world.send_event(SparksRequested { position: Vec3::new(100.0, 0.0, 1.0), theme });
+        app.update();
+
+        let mut placed: Vec<f32> = app.world
+            .query_filtered::<&Transform, (With<PooledEmitter>, With<Playing>)>()
+            .iter(&app.world)
+            .map(|transform| transform.translation.x)
+            .collect();
+        placed.sort_by(f32::total_cmp);
+        assert_eq!(placed, vec![-100.0, 100.0]);
+    }
+}
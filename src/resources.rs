@@ -0,0 +1,916 @@
+use std::{collections::{BTreeMap, BTreeSet, VecDeque}, time::Duration};
+
+use bevy::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    economy::{percent_of, Balance, Difficulty, Economy},
+    locale::Language,
+};
+
+// thin resource wrapper so the economy itself stays plain data
+#[derive(Resource, Clone, Default, Deref, DerefMut, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Score(pub Economy);
+
+// the rules of a run that aren't balance: where it starts and how far it
+// goes. a quick or marathon game is just a different one of these, saved
+// with the game so it stays that kind of game.
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameConfig {
+    // of the balance's win score, before difficulty scales it
+    pub win_percent: u64,
+    pub starting_clicks: u64,
+    // all on the first hand
+    pub starting_fingers: u32,
+    pub starting_buildings: u32,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            win_percent: 100,
+            starting_clicks: 0,
+            starting_fingers: 1,
+            starting_buildings: 1,
+        }
+    }
+}
+
+impl GameConfig {
+    pub fn win_score(&self, economy: &Economy, balance: &Balance) -> u64 {
+        percent_of(economy.win_score(balance), self.win_percent).max(1)
+    }
+
+    // a hand always keeps at least one finger, whatever the config says
+    pub fn first_hand_fingers(&self) -> u32 {
+        self.starting_fingers.max(1)
+    }
+
+    pub fn starting_economy(&self, difficulty: Difficulty, sandbox: bool) -> Economy {
+        Economy {
+            stored_clicks: self.starting_clicks,
+            total_fingers: self.first_hand_fingers() as u64,
+            buildings: self.starting_buildings.max(1),
+            sandbox,
+            difficulty,
+            ..Economy::default()
+        }
+    }
+}
+
+// manual finger clicks landing less than this far apart build a combo
+pub const COMBO_WINDOW_SECS : f32 = 0.5;
+// clicks in a row needed for each extra step of bonus
+pub const COMBO_CLICKS_PER_STEP : u32 = 5;
+pub const COMBO_MAX_BONUS : u64 = 5;
+
+#[derive(Resource)]
+pub struct Combo {
+    pub streak: u32,
+    pub decay: Timer,
+    // the hand clicked last, where the combo is shown
+    pub hand: Option<Entity>,
+}
+
+impl Default for Combo {
+    fn default() -> Self {
+        Combo {
+            streak: 0,
+            decay: Timer::from_seconds(COMBO_WINDOW_SECS, TimerMode::Once),
+            hand: None,
+        }
+    }
+}
+
+impl Combo {
+    pub fn hit(&mut self, hand: Entity) {
+        self.streak = self.streak.saturating_add(1);
+        self.decay.reset();
+        self.hand = Some(hand);
+    }
+
+    // multiplies manual finger clicks, on top of score.multiplier(&balance)
+    pub fn bonus(&self) -> u64 {
+        (1 + (self.streak / COMBO_CLICKS_PER_STEP) as u64).min(COMBO_MAX_BONUS)
+    }
+}
+
+// where a batch of clicks came from
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ClickSource {
+    // fingers clicked by hand
+    Manual,
+    // combined hands clapped by hand
+    Clap,
+    // autoed hands clapping themselves
+    Auto,
+    Golden,
+    // the buildings' passive income
+    Trickle,
+}
+
+impl ClickSource {
+    pub const ALL : [ClickSource; 5] = [ClickSource::Manual, ClickSource::Clap, ClickSource::Auto, ClickSource::Golden, ClickSource::Trickle];
+
+    // for exports, where the name has to stay put across languages
+    pub fn id(self) -> &'static str {
+        match self {
+            ClickSource::Manual => "manual",
+            ClickSource::Clap => "clap",
+            ClickSource::Auto => "auto",
+            ClickSource::Golden => "golden",
+            ClickSource::Trickle => "trickle",
+        }
+    }
+
+    pub fn label_key(self) -> &'static str {
+        match self {
+            ClickSource::Manual => "income.manual",
+            ClickSource::Clap => "income.clap",
+            ClickSource::Auto => "income.auto",
+            ClickSource::Golden => "income.golden",
+            ClickSource::Trickle => "income.trickle",
+        }
+    }
+}
+
+#[derive(Event)]
+pub struct ClicksEmitted {
+    pub amount: u64,
+    pub source: ClickSource,
+    // the hand that produced them, if any
+    pub hand: Option<Entity>,
+}
+
+pub const ABBREVIATION_SUFFIXES : [&str; 6] = ["K", "M", "B", "T", "Qa", "Qi"];
+
+pub const ABBREVIATION_PRECISION : usize = 2;
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum NumberFormat {
+    Commas,
+    #[default]
+    Abbreviated,
+}
+
+// our own copy of bevy's WindowMode so settings can be serialized
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    BorderlessFullscreen,
+    Fullscreen,
+}
+
+impl DisplayMode {
+    pub const ALL : [DisplayMode; 3] = [DisplayMode::Windowed, DisplayMode::BorderlessFullscreen, DisplayMode::Fullscreen];
+
+    pub fn label_key(self) -> &'static str {
+        match self {
+            DisplayMode::Windowed => "display.windowed",
+            DisplayMode::BorderlessFullscreen => "display.borderless_fullscreen",
+            DisplayMode::Fullscreen => "display.fullscreen",
+        }
+    }
+
+    pub fn window_mode(self) -> bevy::window::WindowMode {
+        match self {
+            DisplayMode::Windowed => bevy::window::WindowMode::Windowed,
+            DisplayMode::BorderlessFullscreen => bevy::window::WindowMode::BorderlessFullscreen,
+            DisplayMode::Fullscreen => bevy::window::WindowMode::Fullscreen,
+        }
+    }
+}
+
+// how finished frames reach the screen
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+pub enum Presentation {
+    // waits for the display, which keeps a mostly idle game from running hot
+    #[default]
+    Vsync,
+    Immediate,
+    Mailbox,
+}
+
+impl Presentation {
+    pub const ALL : [Presentation; 3] = [Presentation::Vsync, Presentation::Immediate, Presentation::Mailbox];
+
+    pub fn label_key(self) -> &'static str {
+        match self {
+            Presentation::Vsync => "presentation.vsync",
+            Presentation::Immediate => "presentation.immediate",
+            Presentation::Mailbox => "presentation.mailbox",
+        }
+    }
+
+    // the non-vsync modes fall back to vsync where the platform lacks them
+    pub fn present_mode(self) -> bevy::window::PresentMode {
+        match self {
+            Presentation::Vsync => bevy::window::PresentMode::AutoVsync,
+            Presentation::Immediate => bevy::window::PresentMode::AutoNoVsync,
+            Presentation::Mailbox => bevy::window::PresentMode::Mailbox,
+        }
+    }
+}
+
+// egui's two built-in looks
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl Theme {
+    pub const ALL : [Theme; 2] = [Theme::Dark, Theme::Light];
+
+    pub fn label_key(self) -> &'static str {
+        match self {
+            Theme::Dark => "theme.dark",
+            Theme::Light => "theme.light",
+        }
+    }
+}
+
+// the highest frame cap settings offer
+pub const MAX_FRAME_CAP : u32 = 240;
+
+// actions that can be bound to a key
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hotkey {
+    Click,
+    BuyHand,
+    BuyFinger,
+    SmartBuy,
+    Pause,
+}
+
+impl Hotkey {
+    pub const ALL : [Hotkey; 5] = [Hotkey::Click, Hotkey::BuyHand, Hotkey::BuyFinger, Hotkey::SmartBuy, Hotkey::Pause];
+
+    // names the binding in saves and in `hotkey.<key>` strings
+    pub fn key(self) -> &'static str {
+        match self {
+            Hotkey::Click => "click",
+            Hotkey::BuyHand => "buy_hand",
+            Hotkey::BuyFinger => "buy_finger",
+            Hotkey::SmartBuy => "smart_buy",
+            Hotkey::Pause => "pause",
+        }
+    }
+}
+
+// keys that can be bound. Escape cancels a rebind, F3 stays with the fps
+// overlay and Tab with focus mode, so none of them is offered.
+pub const BINDABLE_KEYS : [KeyCode; 42] = [
+    KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D, KeyCode::E, KeyCode::F, KeyCode::G, KeyCode::H, KeyCode::I,
+    KeyCode::J, KeyCode::K, KeyCode::L, KeyCode::M, KeyCode::N, KeyCode::O, KeyCode::P, KeyCode::Q, KeyCode::R,
+    KeyCode::S, KeyCode::T, KeyCode::U, KeyCode::V, KeyCode::W, KeyCode::X, KeyCode::Y, KeyCode::Z,
+    KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5, KeyCode::Key6,
+    KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+    KeyCode::Space, KeyCode::Return, KeyCode::Up, KeyCode::Down, KeyCode::Left, KeyCode::Right,
+];
+
+// bevy's input types aren't serializable without its serialize feature, so
+// keys are saved by name
+pub fn key_name(key: KeyCode) -> String {
+    format!("{:?}", key)
+}
+
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+    BINDABLE_KEYS.into_iter().find(|key| key_name(*key) == name)
+}
+
+// saved as action name to key name. anything unknown keeps its default, so a
+// hand-edited save can't lose a binding.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "BTreeMap<String, String>", into = "BTreeMap<String, String>")]
+pub struct Keybindings {
+    pub click: KeyCode,
+    pub buy_hand: KeyCode,
+    pub buy_finger: KeyCode,
+    pub smart_buy: KeyCode,
+    pub pause: KeyCode,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Keybindings {
+            click: KeyCode::Space,
+            buy_hand: KeyCode::B,
+            buy_finger: KeyCode::F,
+            smart_buy: KeyCode::S,
+            pause: KeyCode::P,
+        }
+    }
+}
+
+impl Keybindings {
+    pub fn get(&self, hotkey: Hotkey) -> KeyCode {
+        match hotkey {
+            Hotkey::Click => self.click,
+            Hotkey::BuyHand => self.buy_hand,
+            Hotkey::BuyFinger => self.buy_finger,
+            Hotkey::SmartBuy => self.smart_buy,
+            Hotkey::Pause => self.pause,
+        }
+    }
+
+    // a key only does one thing, so whatever had it before gets this
+    // action's old key. returns that action, for telling the player.
+    pub fn set(&mut self, hotkey: Hotkey, key: KeyCode) -> Option<Hotkey> {
+        let old = self.get(hotkey);
+        let displaced = Hotkey::ALL.into_iter().find(|other| *other != hotkey && self.get(*other) == key);
+        if let Some(other) = displaced {
+            *self.get_mut(other) = old;
+        }
+        *self.get_mut(hotkey) = key;
+        displaced
+    }
+
+    fn get_mut(&mut self, hotkey: Hotkey) -> &mut KeyCode {
+        match hotkey {
+            Hotkey::Click => &mut self.click,
+            Hotkey::BuyHand => &mut self.buy_hand,
+            Hotkey::BuyFinger => &mut self.buy_finger,
+            Hotkey::SmartBuy => &mut self.smart_buy,
+            Hotkey::Pause => &mut self.pause,
+        }
+    }
+}
+
+impl From<BTreeMap<String, String>> for Keybindings {
+    fn from(names: BTreeMap<String, String>) -> Self {
+        let mut bindings = Keybindings::default();
+        for hotkey in Hotkey::ALL {
+            if let Some(key) = names.get(hotkey.key()).and_then(|name| key_from_name(name)) {
+                bindings.set(hotkey, key);
+            }
+        }
+        bindings
+    }
+}
+
+impl From<Keybindings> for BTreeMap<String, String> {
+    fn from(bindings: Keybindings) -> Self {
+        Hotkey::ALL.into_iter().map(|hotkey| (hotkey.key().to_string(), key_name(bindings.get(hotkey)))).collect()
+    }
+}
+
+// window sizes offered in settings; the first is bevy's default
+pub const RESOLUTIONS : [(u32, u32); 5] = [(1280, 720), (1366, 768), (1600, 900), (1920, 1080), (2560, 1440)];
+
+#[derive(Resource, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub number_format: NumberFormat,
+    pub master_volume: f32,
+    // scales the spawn rate of newly spawned particle systems
+    pub particle_density: f32,
+    // bigger payouts burst faster, further and for longer
+    pub scaled_bursts: bool,
+    // 0.1-1.0, scales particle counts and how many emitters may run at once
+    pub quality: f32,
+    // follow the frame rate instead of `quality`
+    pub auto_quality: bool,
+    // only every Nth lit pixel of a building emits
+    pub emitter_stride: u32,
+    // one emitter at the middle of each building instead of one per lit
+    // pixel, for slower gpus
+    pub simple_particles: bool,
+    pub screen_shake: bool,
+    // 0-1, scales how far the camera shakes
+    pub shake_intensity: f32,
+    // an overview of the whole skyline in the corner
+    pub minimap: bool,
+    // suggest the next step until the player turns them off
+    pub hints: bool,
+    // ready finger buttons glow on and off
+    pub ready_pulse: bool,
+    // for comfort rather than speed: no screen shake or ready pulse whatever
+    // their own settings say, bursts at a steady color and a capped spawn
+    // rate, and no bigger bursts for bigger payouts
+    pub reduce_motion: bool,
+    // holding the mouse on a finger keeps clicking it whenever it's ready
+    pub hold_to_click: bool,
+    pub ui_scale: f32,
+    // for touchscreens: drawn LARGE_UI_SCALE times bigger, with roomier buttons
+    pub large_ui: bool,
+    pub theme: Theme,
+    // srgb, replacing egui's blue. None keeps the theme's own.
+    pub accent: Option<[u8; 3]>,
+    // seconds between autosaves, 0 disables
+    pub autosave_secs: u32,
+    // minutes without input before dropping to low power, 0 disables
+    pub idle_timeout_mins: u32,
+    // minutes of play between reminders to take a break, 0 disables
+    pub reminder_mins: u32,
+    pub display_mode: DisplayMode,
+    // logical size of the window when windowed
+    pub resolution: (u32, u32),
+    pub presentation: Presentation,
+    // frames per second to stay under, 0 for no cap. native builds only.
+    pub frame_cap: u32,
+    pub language: Language,
+    pub keybindings: Keybindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            number_format: NumberFormat::default(),
+            master_volume: 0.5,
+            particle_density: 1.0,
+            scaled_bursts: true,
+            quality: 1.0,
+            auto_quality: false,
+            emitter_stride: 1,
+            simple_particles: false,
+            screen_shake: true,
+            shake_intensity: 0.5,
+            minimap: true,
+            hints: true,
+            ready_pulse: true,
+            reduce_motion: false,
+            hold_to_click: true,
+            ui_scale: 1.0,
+            large_ui: false,
+            theme: Theme::default(),
+            accent: None,
+            autosave_secs: 60,
+            idle_timeout_mins: 5,
+            reminder_mins: 0,
+            display_mode: DisplayMode::default(),
+            resolution: RESOLUTIONS[0],
+            presentation: Presentation::default(),
+            frame_cap: 0,
+            language: Language::default(),
+            keybindings: Keybindings::default(),
+        }
+    }
+}
+
+// flavor news for each order of magnitude of stored clicks, as locale keys
+pub const HEADLINES : [(u64, &str); 10] = [
+    (1_000, "headline.thousand"),
+    (10_000, "headline.ten_thousand"),
+    (100_000, "headline.hundred_thousand"),
+    (1_000_000, "headline.million"),
+    (10_000_000, "headline.ten_million"),
+    (100_000_000, "headline.hundred_million"),
+    (1_000_000_000, "headline.billion"),
+    (10_000_000_000, "headline.ten_billion"),
+    (100_000_000_000, "headline.hundred_billion"),
+    (1_000_000_000_000, "headline.trillion"),
+];
+
+#[derive(Resource, Default)]
+pub struct Headlines {
+    // thresholds already announced this session, so spending back under one
+    // doesn't bring its headline back
+    pub shown: BTreeSet<u64>,
+    // waiting on the ticker, oldest first
+    pub queue: VecDeque<&'static str>,
+}
+
+impl Headlines {
+    // queues the headline of every threshold crossed on the way up
+    pub fn crossed(&mut self, before: u64, now: u64) {
+        for (threshold, key) in HEADLINES {
+            if before < threshold && threshold <= now && self.shown.insert(threshold) {
+                self.queue.push_back(key);
+            }
+        }
+    }
+}
+
+pub const TOAST_SECS : f32 = 4.0;
+pub const TOAST_FADE_SECS : f32 = 1.0;
+
+// short-lived notifications stacked in the top right corner
+#[derive(Resource, Default)]
+pub struct Toasts(pub VecDeque<(String, Timer)>);
+
+impl Toasts {
+    pub fn push(&mut self, message: impl Into<String>) {
+        self.0.push_back((message.into(), Timer::from_seconds(TOAST_SECS, TimerMode::Once)));
+    }
+}
+
+// entries the event log keeps before dropping its oldest
+pub const EVENT_LOG_LEN : usize = 200;
+
+// what's happened lately, stamped with the play time it happened at
+#[derive(Resource, Default)]
+pub struct EventLog(pub VecDeque<(Duration, String)>);
+
+impl EventLog {
+    pub fn push(&mut self, at: Duration, entry: impl Into<String>) {
+        if self.0.len() >= EVENT_LOG_LEN {
+            self.0.pop_front();
+        }
+        self.0.push_back((at, entry.into()));
+    }
+}
+
+// lifetime totals that survive cashout
+#[derive(Resource, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Statistics {
+    pub lifetime_clicks: u64,
+    pub play_time: Duration,
+    // the highest smoothed clicks per second seen
+    pub peak_cps: f64,
+    // play_time when the break reminder last came up, or is next counted from
+    pub last_reminder: Duration,
+    // how many of the hint steps have been taken, so the early ones don't
+    // come back once a cashout clears the hands and fingers
+    pub hint_steps: usize,
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, (secs / 60) % 60, secs % 60);
+    if hours > 0 {
+        format!("{}h {:02}m {:02}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m {:02}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+// how much bigger large ui draws everything, on top of ui_scale
+pub const LARGE_UI_SCALE : f32 = 1.5;
+
+impl Settings {
+    // logical window pixels per egui point
+    pub fn ui_pixels_per_point(&self) -> f32 {
+        if self.large_ui { self.ui_scale * LARGE_UI_SCALE } else { self.ui_scale }
+    }
+
+    // digit grouping and the decimal point follow the chosen language
+    pub fn format(&self, n: u64) -> String {
+        match self.number_format {
+            NumberFormat::Commas => self.language.group_digits(n),
+            NumberFormat::Abbreviated => abbreviate(n).replace('.', &self.language.decimal_point().to_string()),
+        }
+    }
+}
+
+pub fn abbreviate(n: u64) -> String {
+    abbreviate_with_precision(n, ABBREVIATION_PRECISION)
+}
+
+// truncates rather than rounds so 999_999 never displays as "1000.00K"
+pub fn abbreviate_with_precision(n: u64, precision: usize) -> String {
+    let mut scale = 1u64;
+    let mut suffix = None;
+    for s in ABBREVIATION_SUFFIXES.iter() {
+        if n / scale < 1000 {
+            break;
+        }
+        scale *= 1000;
+        suffix = Some(*s);
+    }
+    match suffix {
+        None => n.to_string(),
+        Some(suffix) => {
+            let whole = n / scale;
+            if precision == 0 {
+                return format!("{}{}", whole, suffix);
+            }
+            let precision = precision.min(18);
+            let fraction = (n % scale) as u128 * 10u128.pow(precision as u32) / scale as u128;
+            format!("{}.{:0width$}{}", whole, fraction, suffix, width = precision)
+        }
+    }
+}
+
+pub const CLICK_RATE_WINDOW_SECS : f32 = 1.0;
+
+// the sum of every hand's HandIncome, and the multiplier it was worked out
+// with, so nothing else has to walk the multiplier table
+#[derive(Resource, Default)]
+pub struct Income {
+    pub multiplier: u64,
+    pub per_second: u64,
+}
+
+// passive income earned but not yet a whole click
+#[derive(Resource, Default)]
+pub struct Trickle {
+    pub fraction: f64,
+}
+
+// real time of the latest keyboard, mouse, touch or gamepad input
+#[derive(Resource, Default)]
+pub struct LastInput(pub Duration);
+
+// set after a stretch without input. the game updates rarely and skips
+// particle bursts, but keeps producing.
+#[derive(Resource, Default)]
+pub struct LowPower(pub bool);
+
+// how much faster timers run while fast forwarding. kept low, since it also
+// scales how much a single frame can produce.
+pub const FAST_FORWARD_SPEED : u32 = 4;
+
+// speeds up clap and finger timers for watching the game run itself
+#[derive(Resource, Default)]
+pub struct FastForward {
+    pub enabled: bool,
+}
+
+impl FastForward {
+    pub fn scale(&self, delta: Duration) -> Duration {
+        if self.enabled { delta * FAST_FORWARD_SPEED } else { delta }
+    }
+}
+
+// time constant of the exponential smoothing applied on top of the window
+pub const CLICK_RATE_SMOOTHING_SECS : f32 = 0.5;
+
+#[derive(Resource, Default, Clone)]
+pub struct ClickRate {
+    // (age in seconds, clicks emitted that frame)
+    samples: VecDeque<(f32, u64)>,
+    smoothed: f64,
+}
+
+impl ClickRate {
+    pub fn advance(&mut self, delta: f32, clicks: u64) {
+        for sample in self.samples.iter_mut() {
+            sample.0 += delta;
+        }
+        while self.samples.front().is_some_and(|(age, _)| *age > CLICK_RATE_WINDOW_SECS) {
+            self.samples.pop_front();
+        }
+        if clicks > 0 {
+            self.samples.push_back((0.0, clicks));
+        }
+
+        let window_rate = self.samples.iter().fold(0u64, |total, (_, clicks)| total.saturating_add(*clicks)) as f64
+            / CLICK_RATE_WINDOW_SECS as f64;
+        let blend = 1.0 - (-delta / CLICK_RATE_SMOOTHING_SECS).exp() as f64;
+        self.smoothed += (window_rate - self.smoothed) * blend;
+    }
+
+    pub fn rate(&self) -> f64 {
+        self.smoothed
+    }
+}
+
+// lifetime clicks by source. like Statistics it survives cashout and only a
+// new game clears it.
+#[derive(Resource, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IncomeBreakdown {
+    manual: u64,
+    clap: u64,
+    auto: u64,
+    golden: u64,
+    trickle: u64,
+    // indexed like ClickSource::ALL
+    #[serde(skip)]
+    pub rates: [ClickRate; 5],
+}
+
+impl IncomeBreakdown {
+    fn total_mut(&mut self, source: ClickSource) -> &mut u64 {
+        match source {
+            ClickSource::Manual => &mut self.manual,
+            ClickSource::Clap => &mut self.clap,
+            ClickSource::Auto => &mut self.auto,
+            ClickSource::Golden => &mut self.golden,
+            ClickSource::Trickle => &mut self.trickle,
+        }
+    }
+
+    pub fn total(&self, source: ClickSource) -> u64 {
+        match source {
+            ClickSource::Manual => self.manual,
+            ClickSource::Clap => self.clap,
+            ClickSource::Auto => self.auto,
+            ClickSource::Golden => self.golden,
+            ClickSource::Trickle => self.trickle,
+        }
+    }
+
+    pub fn add(&mut self, source: ClickSource, clicks: u64) {
+        let total = self.total_mut(source);
+        *total = total.saturating_add(clicks);
+    }
+
+    // fraction of all income that came from `source`
+    pub fn share(&self, source: ClickSource) -> f32 {
+        let all = ClickSource::ALL.iter().fold(0u128, |sum, s| sum + self.total(*s) as u128);
+        if all == 0 {
+            return 0.0;
+        }
+        (self.total(source) as f64 / all as f64) as f32
+    }
+
+    pub fn rate(&self, source: ClickSource) -> f64 {
+        self.rates[source as usize].rate()
+    }
+}
+
+// images the game can't draw properly without
+pub const REQUIRED_IMAGES : [&str; 3] = ["building.png", "spark.png", "backdrop.png"];
+
+// required images that failed to load, in the order they were noticed
+#[derive(Resource, Default)]
+pub struct MissingAssets(pub Vec<String>);
+
+// a sound whose file failed to load is dropped, so the game plays on
+// silently rather than queueing sounds that will never start
+#[derive(Resource, Default)]
+pub struct Sounds {
+    pub click: Option<Handle<AudioSource>>,
+    pub purchase: Option<Handle<AudioSource>>,
+    pub cashout: Option<Handle<AudioSource>>,
+}
+
+impl Sounds {
+    pub fn all_mut(&mut self) -> [&mut Option<Handle<AudioSource>>; 3] {
+        [&mut self.click, &mut self.purchase, &mut self.cashout]
+    }
+}
+
+pub fn play_sound(commands: &mut Commands, sound: Option<&Handle<AudioSource>>) {
+    let Some(sound) = sound else {
+        return;
+    };
+    commands.spawn(AudioBundle {
+        source: sound.clone(),
+        settings: PlaybackSettings::DESPAWN,
+    });
+}
+
+// every bit of gameplay randomness draws from here so runs can be replayed
+// from a seed. the seed is saved, so a loaded game replays from it.
+#[derive(Resource)]
+pub struct GameRng {
+    pub seed: u64,
+    pub rng: StdRng,
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        GameRng { seed, rng: StdRng::seed_from_u64(seed) }
+    }
+
+    pub fn from_entropy() -> Self {
+        GameRng::from_seed(rand::random())
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        GameRng::from_entropy()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_quick_game_starts_ahead_and_wins_sooner() {
+        let balance = Balance::default();
+        let standard = GameConfig::default();
+        assert_eq!(standard.starting_economy(Difficulty::Normal, false), Economy::default());
+
+        let quick = GameConfig { win_percent: 10, starting_clicks: 500, starting_fingers: 3, ..GameConfig::default() };
+        let economy = quick.starting_economy(Difficulty::Hard, true);
+        assert_eq!((economy.stored_clicks, economy.total_fingers, economy.buildings), (500, 3, 1));
+        assert!(economy.sandbox);
+        assert_eq!(quick.win_score(&economy, &balance), standard.win_score(&economy, &balance) / 10);
+
+        let empty = GameConfig { starting_fingers: 0, starting_buildings: 0, ..GameConfig::default() };
+        assert_eq!(empty.first_hand_fingers(), 1);
+        assert_eq!(empty.starting_economy(Difficulty::Normal, false), Economy::default());
+    }
+
+    #[test]
+    fn the_event_log_keeps_only_the_newest() {
+        let mut log = EventLog::default();
+        for n in 0..EVENT_LOG_LEN + 5 {
+            log.push(Duration::from_secs(n as u64), n.to_string());
+        }
+        assert_eq!(log.0.len(), EVENT_LOG_LEN);
+        assert_eq!(log.0.front(), Some(&(Duration::from_secs(5), "5".to_string())));
+        assert_eq!(log.0.back().map(|(_, entry)| entry.as_str()), Some("204"));
+    }
+
+    #[test]
+    fn headlines_fire_once_on_the_way_up() {
+        let mut headlines = Headlines::default();
+        headlines.crossed(0, 999);
+        assert!(headlines.queue.is_empty());
+        headlines.crossed(999, 20_000);
+        assert_eq!(headlines.queue, ["headline.thousand", "headline.ten_thousand"]);
+        // dipping under and climbing back over again stays quiet
+        headlines.crossed(500, 1_500);
+        assert_eq!(headlines.queue.len(), 2);
+    }
+
+    #[test]
+    fn fast_forward_scales_only_while_enabled() {
+        let delta = Duration::from_millis(16);
+        assert_eq!(FastForward::default().scale(delta), delta);
+        assert_eq!(FastForward { enabled: true }.scale(delta), delta * FAST_FORWARD_SPEED);
+    }
+
+    #[test]
+    fn abbreviate_leaves_small_numbers_alone() {
+        assert_eq!(abbreviate(0), "0");
+        assert_eq!(abbreviate(999), "999");
+    }
+
+    #[test]
+    fn abbreviate_suffix_boundaries() {
+        assert_eq!(abbreviate(1_000), "1.00K");
+        assert_eq!(abbreviate(999_999), "999.99K");
+        assert_eq!(abbreviate(1_000_000), "1.00M");
+        assert_eq!(abbreviate(1_250_000), "1.25M");
+        assert_eq!(abbreviate(3_400_000_000), "3.40B");
+        assert_eq!(abbreviate(999_999_999_999), "999.99B");
+        assert_eq!(abbreviate(1_000_000_000_000), "1.00T");
+        assert_eq!(abbreviate(1_000_000_000_000_000), "1.00Qa");
+        assert_eq!(abbreviate(u64::MAX), "18.44Qi");
+    }
+
+    #[test]
+    fn abbreviate_precision_is_configurable() {
+        assert_eq!(abbreviate_with_precision(1_234_567, 0), "1M");
+        assert_eq!(abbreviate_with_precision(1_234_567, 1), "1.2M");
+        assert_eq!(abbreviate_with_precision(1_234_567, 3), "1.234M");
+        assert_eq!(abbreviate_with_precision(1_005_000, 2), "1.00M");
+    }
+
+    #[test]
+    fn combo_bonus_steps_up_and_caps() {
+        let mut combo = Combo::default();
+        assert_eq!(combo.bonus(), 1);
+        for _ in 0..COMBO_CLICKS_PER_STEP {
+            combo.hit(Entity::PLACEHOLDER);
+        }
+        assert_eq!(combo.bonus(), 2);
+        combo.streak = u32::MAX;
+        combo.hit(Entity::PLACEHOLDER);
+        assert_eq!(combo.bonus(), COMBO_MAX_BONUS);
+    }
+
+    #[test]
+    fn durations_are_compact() {
+        assert_eq!(format_duration(Duration::from_secs(42)), "42s");
+        assert_eq!(format_duration(Duration::from_secs(252)), "4m 12s");
+        assert_eq!(format_duration(Duration::from_secs(3_723)), "1h 02m 03s");
+    }
+
+    #[test]
+    fn settings_can_switch_back_to_commas() {
+        let settings = Settings { number_format: NumberFormat::Commas, ..Settings::default() };
+        assert_eq!(settings.format(1_250_000), "1,250,000");
+    }
+
+    #[test]
+    fn income_shares_add_up() {
+        let mut income = IncomeBreakdown::default();
+        assert_eq!(income.share(ClickSource::Manual), 0.0);
+        income.add(ClickSource::Manual, 25);
+        income.add(ClickSource::Clap, 25);
+        income.add(ClickSource::Auto, 50);
+        income.add(ClickSource::Auto, u64::MAX);
+        assert_eq!(income.total(ClickSource::Auto), u64::MAX);
+        let shares: f32 = ClickSource::ALL.iter().map(|source| income.share(*source)).sum();
+        assert!((shares - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn keybindings_swap_and_survive_a_save() {
+        let mut bindings = Keybindings::default();
+        assert_eq!(bindings.set(Hotkey::BuyHand, KeyCode::F), Some(Hotkey::BuyFinger));
+        assert_eq!((bindings.buy_hand, bindings.buy_finger), (KeyCode::F, KeyCode::B));
+        assert_eq!(bindings.set(Hotkey::Pause, KeyCode::Key9), None);
+        let saved = ron::to_string(&bindings).unwrap();
+        assert_eq!(ron::from_str::<Keybindings>(&saved).unwrap(), bindings);
+
+        // unknown actions and keys are ignored, the rest still load
+        let edited: Keybindings = ron::from_str(r#"{"click": "Escape", "smart_buy": "Key5", "jump": "J"}"#).unwrap();
+        assert_eq!(edited, Keybindings { smart_buy: KeyCode::Key5, ..Keybindings::default() });
+    }
+
+    #[test]
+    fn numbers_format_per_language() {
+        let settings = Settings { language: Language::Spanish, ..Settings::default() };
+        assert_eq!(settings.format(1_250_000), "1,25M");
+        let settings = Settings { number_format: NumberFormat::Commas, ..settings };
+        assert_eq!(settings.format(1_250_000), "1.250.000");
+    }
+}
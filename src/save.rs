@@ -0,0 +1,958 @@
+use std::{collections::{BTreeMap, BTreeSet}, io::{Read, Write}, time::Duration};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bevy::{app::AppExit, ecs::system::SystemParam, prelude::*};
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::tasks::{block_on, IoTaskPool, Task};
+use bevy_egui::{egui, EguiContexts};
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    balance::BalanceConfig,
+    components::{AutoBuy, HandName, HandState, HandStats},
+    economy::{golden_click_delay, Achievements, Balance, CpsHistory, GoldenClickSpawner, Purchase, PurchaseApplied},
+    locale::Locale,
+    resources::{ClickSource, GameConfig, GameRng, IncomeBreakdown, Score, Settings, Statistics, Toasts},
+    states::NewGame,
+    ui::WindowLayout,
+};
+
+// where the serialized save lives. the format is the same everywhere, only
+// the storage differs between native and web builds. every write keeps the
+// save it replaces as a backup, for when the new one turns out unreadable.
+pub trait SaveBackend {
+    fn write(&self, data: &str);
+    fn read(&self) -> Option<String>;
+    fn read_backup(&self) -> Option<String>;
+    fn clear(&self);
+    // puts back anything a write that died halfway left behind, before a load
+    fn recover(&self) {}
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub struct FileBackend {
+    path: String,
+}
+
+// each write gets a temp file of its own, so one a dead write left behind
+// is never reused
+#[cfg(not(target_arch = "wasm32"))]
+static NEXT_TEMP : std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileBackend {
+    fn backup_path(&self) -> String {
+        format!("{}.bak", self.path)
+    }
+
+    // the new save is on disk before the old one moves aside, so a crash at
+    // any point leaves at least one of them whole
+    fn replace(&self, data: &str) -> std::io::Result<()> {
+        let temp = format!("{}.{}.tmp", self.path, NEXT_TEMP.fetch_add(1, std::sync::atomic::Ordering::Relaxed));
+        let mut file = std::fs::File::create(&temp)?;
+        file.write_all(data.as_bytes())?;
+        file.sync_all()?;
+        drop(file);
+        match std::fs::rename(&self.path, self.backup_path()) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+        std::fs::rename(&temp, &self.path)
+    }
+
+    // temp files left by writes that never finished, newest first
+    fn leftover_temps(&self) -> Vec<(std::time::SystemTime, std::path::PathBuf)> {
+        let path = std::path::Path::new(&self.path);
+        let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(std::path::Path::new("."));
+        let (Some(name), Ok(entries)) = (path.file_name().and_then(|name| name.to_str()), std::fs::read_dir(dir)) else {
+            return Vec::new();
+        };
+        let prefix = format!("{}.", name);
+        let mut temps: Vec<_> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_str().is_some_and(|file| file.starts_with(&prefix) && file.ends_with(".tmp")))
+            .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+            .collect();
+        temps.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+        temps
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl SaveBackend for FileBackend {
+    fn write(&self, data: &str) {
+        if let Err(err) = self.replace(data) {
+            error!("failed to write save {}: {}", self.path, err);
+        }
+    }
+
+    fn read(&self) -> Option<String> {
+        std::fs::read_to_string(&self.path).ok()
+    }
+
+    fn read_backup(&self) -> Option<String> {
+        std::fs::read_to_string(self.backup_path()).ok()
+    }
+
+    fn clear(&self) {
+        let temps = self.leftover_temps().into_iter().map(|(_, temp)| temp.to_string_lossy().into_owned());
+        for path in [self.path.clone(), self.backup_path()].into_iter().chain(temps) {
+            match std::fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => error!("failed to delete save {}: {}", path, err),
+            }
+        }
+    }
+
+    // a crash between replace's renames leaves the newest save only in its
+    // temp file. one newer than the save finishes what replace started; if
+    // it was cut short instead, load_from falls back to the backup as for
+    // any unreadable save. older ones are stale.
+    fn recover(&self) {
+        let mut temps = self.leftover_temps().into_iter().peekable();
+        let saved = std::fs::metadata(&self.path).and_then(|metadata| metadata.modified()).ok();
+        if let Some((_, newest)) = temps.next_if(|(modified, _)| saved.is_none_or(|saved| *modified > saved)) {
+            let moved = match std::fs::rename(&self.path, self.backup_path()) {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err),
+                _ => std::fs::rename(&newest, &self.path),
+            };
+            match moved {
+                Ok(()) => warn!("recovered a save from {}", newest.display()),
+                Err(err) => error!("failed to recover save from {}: {}", newest.display(), err),
+            }
+        }
+        for (_, stale) in temps {
+            if let Err(err) = std::fs::remove_file(&stale) {
+                error!("failed to delete stale save {}: {}", stale.display(), err);
+            }
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub struct LocalStorageBackend {
+    key: String,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl LocalStorageBackend {
+    fn backup_key(&self) -> String {
+        format!("{}.bak", self.key)
+    }
+
+    fn storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok().flatten()
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl SaveBackend for LocalStorageBackend {
+    fn write(&self, data: &str) {
+        // each set_item is all or nothing, so only the backup needs handling
+        if let Some(previous) = self.read() {
+            if let Some(storage) = Self::storage() {
+                let _ = storage.set_item(&self.backup_key(), &previous);
+            }
+        }
+        let written = Self::storage().map(|storage| storage.set_item(&self.key, data));
+        if !matches!(written, Some(Ok(()))) {
+            error!("failed to write save to localStorage");
+        }
+    }
+
+    fn read(&self) -> Option<String> {
+        Self::storage()?.get_item(&self.key).ok().flatten()
+    }
+
+    fn read_backup(&self) -> Option<String> {
+        Self::storage()?.get_item(&self.backup_key()).ok().flatten()
+    }
+
+    fn clear(&self) {
+        if let Some(storage) = Self::storage() {
+            let _ = storage.remove_item(&self.key);
+            let _ = storage.remove_item(&self.backup_key());
+        }
+    }
+}
+
+pub const SAVE_SLOTS : usize = 4;
+
+// the slot every save and load goes to, picked before the game starts
+#[derive(Resource, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct ActiveSlot(pub usize);
+
+impl ActiveSlot {
+    // the first slot keeps the name the single save had, so it carries over
+    fn name(self) -> String {
+        match self.0 {
+            0 => "save".to_string(),
+            slot => format!("save{}", slot + 1),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn backend(self) -> FileBackend {
+        FileBackend { path: format!("{}.ron", self.name()) }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn backend(self) -> LocalStorageBackend {
+        LocalStorageBackend { key: format!("bevy_clicker_{}", self.name()) }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SavedHand {
+    pub state: HandState,
+    pub fingers: u32,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub auto_buy: AutoBuy,
+    // clicks produced over the hand's lifetime
+    #[serde(default)]
+    pub emitted: u64,
+}
+
+// missing fields fall back to their defaults so older saves keep loading
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct SaveData {
+    pub score: Score,
+    pub config: GameConfig,
+    pub settings: Settings,
+    pub hands: Vec<SavedHand>,
+    pub statistics: Statistics,
+    pub income: IncomeBreakdown,
+    // ids of unlocked achievements
+    pub achievements: Vec<String>,
+    pub seen_tutorial: bool,
+    // window key to top-left corner
+    pub windows: BTreeMap<String, (f32, f32)>,
+    pub collapsed_windows: BTreeSet<String>,
+    // None for saves from before runs were seeded
+    pub rng_seed: Option<u64>,
+    // seconds since 1970 when this was written, None where there's no clock
+    pub saved_at: Option<u64>,
+    // set on load when the save was unreadable and this came from its backup
+    #[serde(skip)]
+    pub recovered_from_backup: bool,
+}
+
+// hands waiting to be spawned when the game state is entered
+#[derive(Resource, Default)]
+pub struct SavedHands(pub Vec<SavedHand>);
+
+pub fn load(slot: ActiveSlot) -> SaveData {
+    load_from(&slot.backend())
+}
+
+// each slot's save, or None for an empty one
+pub fn load_slots() -> Vec<Option<SaveData>> {
+    (0..SAVE_SLOTS)
+        .map(|slot| {
+            let backend = ActiveSlot(slot).backend();
+            (backend.read().is_some() || backend.read_backup().is_some()).then(|| load_from(&backend))
+        })
+        .collect()
+}
+
+// what the slot picker shows for a slot
+#[derive(Clone, Debug, PartialEq)]
+pub struct SlotSummary {
+    pub play_time: Duration,
+    pub buildings: u32,
+    pub saved_at: Option<u64>,
+}
+
+impl From<&SaveData> for SlotSummary {
+    fn from(data: &SaveData) -> Self {
+        SlotSummary { play_time: data.statistics.play_time, buildings: data.score.buildings, saved_at: data.saved_at }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct SlotSummaries(pub Vec<Option<SlotSummary>>);
+
+impl SlotSummaries {
+    pub fn from_saves(saves: &[Option<SaveData>]) -> Self {
+        SlotSummaries(saves.iter().map(|save| save.as_ref().map(SlotSummary::from)).collect())
+    }
+}
+
+// the save played most recently, whose settings the slot picker borrows
+pub fn latest(saves: &[Option<SaveData>]) -> Option<&SaveData> {
+    saves.iter().flatten().max_by_key(|save| save.saved_at)
+}
+
+pub fn unix_now() -> Option<u64> {
+    #[cfg(not(target_arch = "wasm32"))]
+    return std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).ok().map(|since| since.as_secs());
+    // std has no clock on the web
+    #[cfg(target_arch = "wasm32")]
+    return None;
+}
+
+// swaps in everything a save restores, for the game about to start
+pub fn restore(commands: &mut Commands, slot: ActiveSlot, data: SaveData) {
+    let mut rng = data.rng_seed.map_or_else(GameRng::default, GameRng::from_seed);
+    // drawn again from the restored seed, so the run replays the same
+    commands.insert_resource(GoldenClickSpawner(golden_click_delay(&mut rng)));
+    commands.insert_resource(rng);
+    commands.insert_resource(slot);
+    commands.insert_resource(RecoveredSave(data.recovered_from_backup));
+    commands.insert_resource(data.score);
+    commands.insert_resource(data.config);
+    commands.insert_resource(Locale::new(data.settings.language));
+    commands.insert_resource(data.settings);
+    commands.insert_resource(SavedHands(data.hands));
+    commands.insert_resource(WindowLayout { positions: data.windows, collapsed: data.collapsed_windows });
+    commands.insert_resource(data.statistics);
+    commands.insert_resource(data.income);
+    commands.insert_resource(Achievements::from_unlocked(&data.achievements));
+}
+
+fn load_from(backend: &impl SaveBackend) -> SaveData {
+    backend.recover();
+    let primary = backend.read();
+    if let Some(contents) = &primary {
+        match ron::from_str(contents) {
+            Ok(data) => return data,
+            Err(err) => warn!("save is unreadable, trying its backup: {}", err),
+        }
+    }
+    // no save but a backup means a write died between moving the old save
+    // aside and putting the new one in place
+    match backend.read_backup().map(|contents| ron::from_str::<SaveData>(&contents)) {
+        Some(Ok(data)) => {
+            warn!("recovered the game from the backup save");
+            SaveData { recovered_from_backup: true, ..data }
+        }
+        Some(Err(err)) => {
+            warn!("ignoring unreadable backup save: {}", err);
+            SaveData::default()
+        }
+        None => SaveData::default(),
+    }
+}
+
+// whether this session's game came out of the backup save
+#[derive(Resource, Default)]
+pub struct RecoveredSave(pub bool);
+
+// tells the player once, since what they see may be a save behind
+pub fn recovered_save_notice_system(
+    mut recovered: ResMut<RecoveredSave>,
+    mut toasts: ResMut<Toasts>,
+    locale: Res<Locale>,
+) {
+    if recovered.0 {
+        toasts.push(locale.t("toast.recovered_save"));
+        recovered.0 = false;
+    }
+}
+
+fn serialize(data: &SaveData) -> Option<String> {
+    ron::ser::to_string_pretty(data, ron::ser::PrettyConfig::default())
+        .map_err(|err| error!("failed to serialize save: {}", err))
+        .ok()
+}
+
+pub fn clear(slot: ActiveSlot) {
+    slot.backend().clear();
+}
+
+// shared saves look like `clicker:1:<base64 of deflated ron>`. the version
+// goes up whenever an older build couldn't make sense of a newer save.
+const EXPORT_TAG : &str = "clicker";
+pub const EXPORT_VERSION : u32 = 1;
+// inflating stops here, so a hostile string can't eat all the memory
+const EXPORT_MAX_BYTES : u64 = 16 * 1024 * 1024;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ImportError {
+    NotAnExport,
+    UnsupportedVersion(u32),
+    Corrupt,
+}
+
+impl ImportError {
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            ImportError::NotAnExport => "share.error.not_an_export",
+            ImportError::UnsupportedVersion(_) => "share.error.unsupported_version",
+            ImportError::Corrupt => "share.error.corrupt",
+        }
+    }
+}
+
+pub fn export_string(data: &SaveData) -> Option<String> {
+    let ron = ron::to_string(data)
+        .map_err(|err| error!("failed to serialize export: {}", err))
+        .ok()?;
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(ron.as_bytes()).ok()?;
+    let compressed = encoder.finish().ok()?;
+    Some(format!("{}:{}:{}", EXPORT_TAG, EXPORT_VERSION, STANDARD.encode(compressed)))
+}
+
+pub fn import_string(text: &str) -> Result<SaveData, ImportError> {
+    let mut parts = text.trim().splitn(3, ':');
+    if parts.next() != Some(EXPORT_TAG) {
+        return Err(ImportError::NotAnExport);
+    }
+    let version = parts.next().and_then(|version| version.parse().ok()).ok_or(ImportError::NotAnExport)?;
+    if version != EXPORT_VERSION {
+        return Err(ImportError::UnsupportedVersion(version));
+    }
+    // pasting can pick up line breaks along the way
+    let encoded: String = parts.next().unwrap_or_default().split_whitespace().collect();
+    let compressed = STANDARD.decode(encoded).map_err(|_| ImportError::Corrupt)?;
+    let mut ron = String::new();
+    DeflateDecoder::new(compressed.as_slice())
+        .take(EXPORT_MAX_BYTES)
+        .read_to_string(&mut ron)
+        .map_err(|_| ImportError::Corrupt)?;
+    ron::from_str(&ron).map_err(|_| ImportError::Corrupt)
+}
+
+// statistics for spreadsheets and scripts, rather than for loading back in
+#[derive(Serialize, Debug)]
+pub struct StatsExport {
+    // seconds since 1970, None where there's no clock
+    pub exported_at: Option<u64>,
+    pub lifetime_clicks: u64,
+    pub play_time_secs: f64,
+    pub peak_cps: f64,
+    pub stored_clicks: u64,
+    pub buildings: u32,
+    pub multiplier: u64,
+    pub influence: u64,
+    // total clicks by ClickSource::id
+    pub income: BTreeMap<&'static str, u64>,
+    // clicks per second, a sample a second, oldest first
+    pub cps_history: Vec<f64>,
+}
+
+impl StatsExport {
+    pub fn new(statistics: &Statistics, score: &Score, balance: &Balance, income: &IncomeBreakdown, cps_history: &CpsHistory) -> Self {
+        StatsExport {
+            exported_at: unix_now(),
+            lifetime_clicks: statistics.lifetime_clicks,
+            play_time_secs: statistics.play_time.as_secs_f64(),
+            peak_cps: statistics.peak_cps,
+            stored_clicks: score.stored_clicks,
+            buildings: score.buildings,
+            multiplier: score.multiplier(balance),
+            influence: score.influence,
+            income: ClickSource::ALL.iter().map(|source| (source.id(), income.total(*source))).collect(),
+            cps_history: cps_history.samples.iter().copied().collect(),
+        }
+    }
+
+    pub fn to_json(&self) -> Option<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|err| error!("failed to serialize statistics: {}", err))
+            .ok()
+    }
+
+    // one `stat,value` row each. none of the names need quoting.
+    pub fn to_csv(&self) -> String {
+        let mut rows = vec![
+            ("exported_at".to_string(), self.exported_at.map_or_else(String::new, |at| at.to_string())),
+            ("lifetime_clicks".to_string(), self.lifetime_clicks.to_string()),
+            ("play_time_secs".to_string(), self.play_time_secs.to_string()),
+            ("peak_cps".to_string(), self.peak_cps.to_string()),
+            ("stored_clicks".to_string(), self.stored_clicks.to_string()),
+            ("buildings".to_string(), self.buildings.to_string()),
+            ("multiplier".to_string(), self.multiplier.to_string()),
+            ("influence".to_string(), self.influence.to_string()),
+        ];
+        rows.extend(self.income.iter().map(|(source, total)| (format!("income.{}", source), total.to_string())));
+        rows.extend(self.cps_history.iter().enumerate().map(|(idx, cps)| (format!("cps_history.{}", idx), cps.to_string())));
+        rows.iter().fold("stat,value\n".to_string(), |csv, (stat, value)| csv + stat + "," + value + "\n")
+    }
+}
+
+// replaces the game in progress with an imported one
+#[derive(Event)]
+pub struct GameImported(pub Box<SaveData>);
+
+// how long a cashout can be taken back
+pub const CASHOUT_UNDO_SECS : f32 = 10.0;
+
+// the game as it was just before the last cashout, kept as save text so that
+// undoing it is the same as importing a save. dropped once the grace window
+// runs out or anything else is bought.
+#[derive(Resource, Default)]
+pub struct CashoutUndo {
+    pub snapshot: Option<(String, Timer)>,
+}
+
+impl CashoutUndo {
+    pub fn remaining_secs(&self) -> Option<f32> {
+        self.snapshot.as_ref().map(|(_, timer)| timer.remaining_secs())
+    }
+}
+
+// puts the game back the way it was before the last cashout
+#[derive(Event)]
+pub struct UndoCashout;
+
+// asks for the game to be saved at the end of the frame
+#[derive(Event)]
+pub struct SaveRequested;
+
+#[derive(Resource, Default)]
+pub struct SaveStatus {
+    // Time::elapsed() when the last save was written
+    pub last_saved: Option<Duration>,
+}
+
+// the save write on the io pool, if one is still going, and the newest
+// snapshot queued behind it. only one write runs at a time, so an older
+// snapshot can never land on top of a newer one.
+#[derive(Resource, Default)]
+pub struct SaveWriter {
+    #[cfg(not(target_arch = "wasm32"))]
+    writing: Option<Task<()>>,
+    waiting: Option<(Box<dyn SaveBackend + Send + Sync>, String)>,
+}
+
+impl SaveWriter {
+    // replaces whatever was still waiting, it's out of date now
+    fn queue(&mut self, backend: impl SaveBackend + Send + Sync + 'static, contents: String) {
+        self.waiting = Some((Box::new(backend), contents));
+    }
+
+    // starts the waiting write once the last one is done. on exit it writes
+    // right away instead, a background task might never finish.
+    #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+    fn pump(&mut self, exiting: bool) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if !exiting && self.writing.as_ref().is_some_and(|task| !task.is_finished()) {
+                return;
+            }
+            if let Some(task) = self.writing.take() {
+                block_on(task);
+            }
+        }
+        let Some((backend, contents)) = self.waiting.take() else {
+            return;
+        };
+        // local storage writes don't block anyway, the web build does them inline
+        #[cfg(not(target_arch = "wasm32"))]
+        if !exiting {
+            self.writing = Some(IoTaskPool::get().spawn(async move { backend.write(&contents) }));
+            return;
+        }
+        backend.write(&contents);
+    }
+}
+
+type SavedHandQuery<'w, 's> = Query<'w, 's, (
+    Entity,
+    &'static HandState,
+    Option<&'static Children>,
+    &'static HandName,
+    &'static AutoBuy,
+    &'static HandStats,
+)>;
+
+#[derive(SystemParam)]
+pub struct GameSnapshot<'w, 's> {
+    score: Res<'w, Score>,
+    config: Res<'w, GameConfig>,
+    settings: Res<'w, Settings>,
+    statistics: Res<'w, Statistics>,
+    income: Res<'w, IncomeBreakdown>,
+    achievements: Res<'w, Achievements>,
+    rng: Res<'w, GameRng>,
+    hands: SavedHandQuery<'w, 's>,
+    contexts: EguiContexts<'w, 's>,
+}
+
+impl GameSnapshot<'_, '_> {
+    // the snapshot borrows egui for window positions, so systems that also
+    // draw have to go through it
+    pub fn ctx_mut(&mut self) -> &mut egui::Context {
+        self.contexts.ctx_mut()
+    }
+
+    pub fn capture(&mut self) -> SaveData {
+        let ctx = self.contexts.ctx_mut();
+        let hand_ids = || self.hands.iter().map(|(entity, ..)| egui::Id::new(entity));
+        SaveData {
+            score: self.score.clone(),
+            config: self.config.clone(),
+            settings: self.settings.clone(),
+            hands: self.hands
+                .iter()
+                .map(|(_, state, clickers, name, auto_buy, stats)| SavedHand {
+                    state: *state,
+                    fingers: clickers.map_or(0, |c| c.len() as u32),
+                    name: name.0.clone(),
+                    auto_buy: *auto_buy,
+                    emitted: stats.emitted,
+                })
+                .collect(),
+            statistics: self.statistics.clone(),
+            income: self.income.clone(),
+            achievements: self.achievements.unlocked_ids(),
+            // we only save once the game has started, so the intro is behind us
+            seen_tutorial: true,
+            windows: WindowLayout::capture(ctx, hand_ids()),
+            collapsed_windows: WindowLayout::capture_collapsed(ctx, hand_ids()),
+            rng_seed: Some(self.rng.seed),
+            saved_at: unix_now(),
+            recovered_from_backup: false,
+        }
+    }
+}
+
+// has to see the cashout before apply_purchase_system carries it out
+#[allow(clippy::too_many_arguments)]
+pub fn snapshot_cashout_system(
+    mut purchases: EventReader<Purchase>,
+    mut new_games: EventReader<NewGame>,
+    mut imports: EventReader<GameImported>,
+    mut snapshot: GameSnapshot,
+    mut undo: ResMut<CashoutUndo>,
+    score: Res<Score>,
+    balance: Res<BalanceConfig>,
+) {
+    // undoing past another game would quietly lose it
+    if new_games.read().count() > 0 || imports.read().count() > 0 {
+        undo.snapshot = None;
+    }
+    for purchase in purchases.read() {
+        if *purchase == Purchase::Cashout && score.can_cash_out(&balance) {
+            undo.snapshot = serialize(&snapshot.capture())
+                .map(|contents| (contents, Timer::from_seconds(CASHOUT_UNDO_SECS, TimerMode::Once)));
+        }
+    }
+}
+
+// undoing past a later purchase would quietly lose it too. only the ones that
+// went through count, so a click on something unaffordable keeps the undo.
+pub fn forget_cashout_undo_system(mut applied: EventReader<PurchaseApplied>, mut undo: ResMut<CashoutUndo>) {
+    if applied.read().any(|PurchaseApplied(purchase)| *purchase != Purchase::Cashout) {
+        undo.snapshot = None;
+    }
+}
+
+pub fn undo_cashout_system(
+    mut undo_events: EventReader<UndoCashout>,
+    mut undo: ResMut<CashoutUndo>,
+    mut import_events: EventWriter<GameImported>,
+    time: Res<Time>,
+) {
+    if undo_events.read().count() > 0 {
+        if let Some((contents, _)) = undo.snapshot.take() {
+            match ron::from_str::<SaveData>(&contents) {
+                Ok(data) => import_events.send(GameImported(Box::new(data))),
+                Err(err) => error!("can't read back the cashout snapshot: {}", err),
+            }
+        }
+    }
+    if let Some((_, timer)) = &mut undo.snapshot {
+        if timer.tick(time.delta()).finished() {
+            undo.snapshot = None;
+        }
+    }
+}
+
+pub fn autosave_system(
+    settings: Res<Settings>,
+    mut timer: Local<Timer>,
+    time: Res<Time>,
+    mut save_events: EventWriter<SaveRequested>,
+) {
+    // 0 turns autosave off
+    if settings.autosave_secs == 0 {
+        return;
+    }
+    let interval = Duration::from_secs(settings.autosave_secs as u64);
+    if timer.duration() != interval {
+        *timer = Timer::new(interval, TimerMode::Repeating);
+    }
+    if timer.tick(time.delta()).just_finished() {
+        save_events.send(SaveRequested);
+    }
+}
+
+// saves on request and on exit. snapshotting has to happen here, but the
+// file write is handed to the io pool so a slow disk doesn't stall the frame.
+// saves asked for while a write is going wait for it, only the newest kept.
+pub fn save_game_system(
+    mut save_events: EventReader<SaveRequested>,
+    exit_events: EventReader<AppExit>,
+    mut snapshot: GameSnapshot,
+    mut status: ResMut<SaveStatus>,
+    mut writer: ResMut<SaveWriter>,
+    slot: Res<ActiveSlot>,
+    time: Res<Time>,
+) {
+    let exiting = !exit_events.is_empty();
+    if save_events.read().count() > 0 || exiting {
+        if let Some(contents) = serialize(&snapshot.capture()) {
+            writer.queue(slot.backend(), contents);
+            status.last_saved = Some(time.elapsed());
+        }
+    }
+    writer.pump(exiting);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exports_round_trip() {
+        let mut data = SaveData::default();
+        data.score.stored_clicks = 12_345;
+        data.achievements = vec!["first_finger".to_string()];
+        let text = export_string(&data).unwrap();
+        assert!(text.starts_with("clicker:1:"));
+        let imported = import_string(&format!("  {}\n", text)).unwrap();
+        assert_eq!(imported.score.stored_clicks, 12_345);
+        assert_eq!(imported.achievements, data.achievements);
+    }
+
+    // a save file of its own under the temp dir, cleared before and after
+    struct TempSave(String);
+
+    impl TempSave {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("bevy_clicker_{}_{}.ron", name, std::process::id()));
+            let save = TempSave(path.to_string_lossy().into_owned());
+            save.backend().clear();
+            save
+        }
+
+        fn backend(&self) -> FileBackend {
+            FileBackend { path: self.0.clone() }
+        }
+    }
+
+    impl Drop for TempSave {
+        fn drop(&mut self) {
+            self.backend().clear();
+        }
+    }
+
+    fn with_clicks(stored_clicks: u64) -> String {
+        let mut data = SaveData::default();
+        data.score.stored_clicks = stored_clicks;
+        serialize(&data).unwrap()
+    }
+
+    #[test]
+    fn writes_keep_the_previous_save_as_a_backup() {
+        let save = TempSave::new("rotation");
+        let backend = save.backend();
+        backend.write(&with_clicks(1));
+        assert_eq!(backend.read_backup(), None);
+        backend.write(&with_clicks(2));
+        assert_eq!(backend.read(), Some(with_clicks(2)));
+        assert_eq!(backend.read_backup(), Some(with_clicks(1)));
+        let loaded = load_from(&backend);
+        assert_eq!(loaded.score.stored_clicks, 2);
+        assert!(!loaded.recovered_from_backup);
+    }
+
+    #[test]
+    fn saves_wait_for_the_write_in_flight_and_only_the_newest_is_kept() {
+        let save = TempSave::new("one_writer");
+        let mut writer = SaveWriter::default();
+        bevy::tasks::IoTaskPool::get_or_init(bevy::tasks::TaskPool::default);
+        // a write that hasn't finished yet
+        writer.writing = Some(IoTaskPool::get().spawn(std::future::pending()));
+        writer.queue(save.backend(), with_clicks(1));
+        writer.pump(false);
+        writer.queue(save.backend(), with_clicks(2));
+        writer.pump(false);
+        assert_eq!(save.backend().read(), None);
+
+        writer.writing = None;
+        writer.pump(false);
+        assert!(writer.waiting.is_none());
+        // exiting waits for the write that just started
+        writer.pump(true);
+        assert_eq!(save.backend().read(), Some(with_clicks(2)));
+        assert_eq!(save.backend().read_backup(), None);
+    }
+
+    #[test]
+    fn a_truncated_save_recovers_from_its_backup() {
+        let save = TempSave::new("truncated");
+        let backend = save.backend();
+        backend.write(&with_clicks(1));
+        let contents = with_clicks(2);
+        backend.write(&contents);
+        // as if the process died halfway through writing
+        std::fs::write(&save.0, &contents[..contents.len() / 2]).unwrap();
+        let loaded = load_from(&backend);
+        assert_eq!(loaded.score.stored_clicks, 1);
+        assert!(loaded.recovered_from_backup);
+
+        // and a write that died between the renames
+        std::fs::remove_file(&save.0).unwrap();
+        assert_eq!(load_from(&backend).score.stored_clicks, 1);
+
+        // with nothing readable at all, it's a fresh game
+        std::fs::write(backend.backup_path(), "garbage").unwrap();
+        let loaded = load_from(&backend);
+        assert_eq!(loaded.score.stored_clicks, 0);
+        assert!(!loaded.recovered_from_backup);
+    }
+
+    #[test]
+    fn a_write_that_died_between_renames_is_finished_on_load() {
+        let save = TempSave::new("renames");
+        let backend = save.backend();
+        backend.write(&with_clicks(1));
+        backend.write(&with_clicks(2));
+        // an older temp from some other failed write, then the newest save
+        // left in its temp file with the previous one already moved aside
+        std::fs::write(format!("{}.7.tmp", save.0), with_clicks(0)).unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        std::fs::rename(&save.0, backend.backup_path()).unwrap();
+        std::fs::write(format!("{}.8.tmp", save.0), with_clicks(3)).unwrap();
+
+        let loaded = load_from(&backend);
+        assert_eq!(loaded.score.stored_clicks, 3);
+        assert!(!loaded.recovered_from_backup);
+        assert_eq!(backend.read_backup(), Some(with_clicks(2)));
+        assert!(backend.leftover_temps().is_empty());
+
+        // a temp older than the save it would replace is only cleaned up
+        std::thread::sleep(Duration::from_millis(20));
+        std::fs::write(format!("{}.9.tmp", save.0), with_clicks(4)).unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        backend.write(&with_clicks(5));
+        assert_eq!(load_from(&backend).score.stored_clicks, 5);
+        assert!(backend.leftover_temps().is_empty());
+    }
+
+    fn undo_app(stored_clicks: u64) -> App {
+        let mut app = crate::testing::test_app();
+        add_undo(&mut app, stored_clicks);
+        app
+    }
+
+    fn add_undo(app: &mut App, stored_clicks: u64) {
+        app.add_event::<UndoCashout>()
+            .add_event::<GameImported>()
+            .insert_resource(CashoutUndo { snapshot: Some((with_clicks(stored_clicks), Timer::from_seconds(CASHOUT_UNDO_SECS, TimerMode::Once))) })
+            .insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(Duration::from_millis(250)))
+            .add_systems(Update, undo_cashout_system);
+    }
+
+    fn imported_clicks(app: &mut App) -> Vec<u64> {
+        let events = app.world.resource::<Events<GameImported>>();
+        events.get_reader().read(events).map(|GameImported(data)| data.score.stored_clicks).collect()
+    }
+
+    #[test]
+    fn undoing_a_cashout_imports_the_snapshot_once() {
+        let mut app = undo_app(1_234);
+        app.update();
+        app.world.send_event(UndoCashout);
+        app.update();
+        assert_eq!(imported_clicks(&mut app), vec![1_234]);
+        assert!(app.world.resource::<CashoutUndo>().snapshot.is_none());
+
+        // a second press has nothing left to undo
+        app.world.send_event(UndoCashout);
+        app.update();
+        assert_eq!(imported_clicks(&mut app), vec![1_234]);
+    }
+
+    #[test]
+    fn only_purchases_that_go_through_drop_the_undo() {
+        let mut app = crate::testing::purchase_app();
+        add_undo(&mut app, 1_234);
+        app.add_systems(Update, forget_cashout_undo_system.after(crate::economy::apply_purchase_system));
+        app.update();
+        // nothing in the bank to pay for it
+        app.world.send_event(Purchase::Hand { count: 1 });
+        app.update();
+        assert!(app.world.resource::<CashoutUndo>().snapshot.is_some());
+
+        app.world.resource_mut::<Score>().stored_clicks = 1_000;
+        app.world.send_event(Purchase::Hand { count: 1 });
+        app.update();
+        assert!(app.world.resource::<CashoutUndo>().snapshot.is_none());
+    }
+
+    #[test]
+    fn the_cashout_snapshot_expires() {
+        let mut app = undo_app(1_234);
+        // four frames a second
+        for _ in 0..CASHOUT_UNDO_SECS as u32 * 2 {
+            app.update();
+        }
+        assert!(app.world.resource::<CashoutUndo>().snapshot.is_some());
+        for _ in 0..CASHOUT_UNDO_SECS as u32 * 2 + 1 {
+            app.update();
+        }
+        assert!(app.world.resource::<CashoutUndo>().snapshot.is_none());
+        app.world.send_event(UndoCashout);
+        app.update();
+        assert!(imported_clicks(&mut app).is_empty());
+    }
+
+    #[test]
+    fn slots_keep_apart_and_the_first_is_the_old_save() {
+        assert_eq!(ActiveSlot(0).backend().path, "save.ron");
+        let paths: BTreeSet<String> = (0..SAVE_SLOTS).map(|slot| ActiveSlot(slot).backend().path).collect();
+        assert_eq!(paths.len(), SAVE_SLOTS);
+    }
+
+    #[test]
+    fn the_latest_save_is_the_last_written() {
+        let at = |saved_at| Some(SaveData { saved_at, ..SaveData::default() });
+        let saves = [at(Some(5)), None, at(Some(9)), at(None)];
+        assert_eq!(latest(&saves).and_then(|save| save.saved_at), Some(9));
+        let summaries = SlotSummaries::from_saves(&saves);
+        assert_eq!(summaries.0[1], None);
+        assert_eq!(summaries.0[2].as_ref().map(|summary| summary.buildings), Some(1));
+        assert_eq!(latest(&[None, None]).map(|save| save.saved_at), None);
+    }
+
+    #[test]
+    fn statistics_export_as_json_and_csv() {
+        use crate::economy::Economy;
+
+        let score = Score(Economy { stored_clicks: 1_500, ..Economy::default() });
+        let mut income = IncomeBreakdown::default();
+        income.add(ClickSource::Golden, 300);
+        let statistics = Statistics { lifetime_clicks: 2_000, ..Statistics::default() };
+        let export = StatsExport::new(&statistics, &score, &Balance::default(), &income, &CpsHistory::default());
+
+        let json: serde_json::Value = serde_json::from_str(&export.to_json().unwrap()).unwrap();
+        assert_eq!(json["lifetime_clicks"], 2_000);
+        assert_eq!(json["income"]["golden"], 300);
+        assert_eq!(json["cps_history"], serde_json::json!([]));
+
+        let csv = export.to_csv();
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows[0], "stat,value");
+        assert!(rows.contains(&"stored_clicks,1500"));
+        assert!(rows.contains(&"income.golden,300"));
+        assert!(rows.iter().all(|row| row.split(',').count() == 2));
+    }
+
+    #[test]
+    fn bad_imports_are_rejected() {
+        assert_eq!(import_string("hello").err(), Some(ImportError::NotAnExport));
+        assert_eq!(import_string("clicker:99:AAAA").err(), Some(ImportError::UnsupportedVersion(99)));
+        assert_eq!(import_string("clicker:1:!!!").err(), Some(ImportError::Corrupt));
+        assert_eq!(import_string("clicker:1:AAAA").err(), Some(ImportError::Corrupt));
+    }
+}
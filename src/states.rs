@@ -0,0 +1,323 @@
+use bevy::{asset::LoadState, prelude::*};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    buildings::Building,
+    components::{default_hand_name, spawn_hand, HandLabel, HandState, HandStats},
+    economy::{golden_click_delay, Achievements, CpsHistory, Difficulty, GoldenClick, GoldenClickSpawner},
+    locale::Locale,
+    particles::spawn_particle_pool,
+    resources::{format_duration, ClickRate, GameConfig, GameRng, IncomeBreakdown, Score, Settings, Sounds, Statistics},
+    save::{self, ActiveSlot, GameImported, SaveData, SaveRequested, SavedHand, SavedHands, SlotSummaries},
+    ui::difficulty_picker,
+};
+
+pub struct GameStatesPlugin;
+
+impl Plugin for GameStatesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<State>()
+            .add_event::<NewGame>()
+            .add_event::<GameImported>()
+            .add_systems(Update, slot_picker_window.run_if(in_state(State::Slots)))
+            .add_systems(Update, welcome_window.run_if(in_state(State::Welcome)))
+            // not OnEnter, since continuing after a win comes back into Game
+            .add_systems(OnTransition { from: State::Welcome, to: State::Game }, setup)
+            .add_systems(OnTransition { from: State::Slots, to: State::Game }, setup)
+            .add_systems(Update, (new_game_system, drop_failed_sounds_system).run_if(in_state(State::Game)))
+            .add_systems(Update, win_window.run_if(in_state(State::Finished)));
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug, States, Default)]
+pub enum State {
+    #[default]
+    Slots,
+    Welcome,
+    Game,
+    Finished,
+}
+
+// the difficulty is picked here and can't be changed until the next one
+#[derive(Event)]
+pub struct NewGame {
+    pub difficulty: Difficulty,
+}
+
+// back to where the game config starts a run, as if the game had never been
+// played, or over to an imported game and its config. settings are
+// preferences rather than progress, so they survive either way, and a new
+// game stays a sandbox game if this one was.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn new_game_system(
+    mut new_game_events: EventReader<NewGame>,
+    mut import_events: EventReader<GameImported>,
+    mut save_events: EventWriter<SaveRequested>,
+    mut commands: Commands,
+    resettable: Query<Entity, Or<(With<HandLabel>, With<Building>, With<GoldenClick>)>>,
+    mut score: ResMut<Score>,
+    mut statistics: ResMut<Statistics>,
+    mut income: ResMut<IncomeBreakdown>,
+    mut achievements: ResMut<Achievements>,
+    mut click_rate: ResMut<ClickRate>,
+    mut cps_history: ResMut<CpsHistory>,
+    mut rng: ResMut<GameRng>,
+    mut golden_spawner: ResMut<GoldenClickSpawner>,
+    mut config: ResMut<GameConfig>,
+    slot: Res<ActiveSlot>,
+    locale: Res<Locale>,
+) {
+    let started = new_game_events.read().last().map(|new_game| new_game.difficulty);
+    let imported = import_events.read().last();
+    if started.is_none() && imported.is_none() {
+        return;
+    }
+    for entity in &resettable {
+        commands.entity(entity).despawn_recursive();
+    }
+    *click_rate = ClickRate::default();
+    *cps_history = CpsHistory::default();
+    match imported {
+        Some(GameImported(data)) => {
+            *score = data.score.clone();
+            *config = data.config.clone();
+            *statistics = data.statistics.clone();
+            *income = data.income.clone();
+            *achievements = Achievements::from_unlocked(&data.achievements);
+            *rng = data.rng_seed.map_or_else(GameRng::from_entropy, GameRng::from_seed);
+            spawn_saved_hands(&mut commands, data.hands.clone(), &score, &config, &locale);
+            save_events.send(SaveRequested);
+        }
+        None => {
+            let difficulty = started.unwrap_or_default();
+            *score = Score(config.starting_economy(difficulty, score.sandbox));
+            *statistics = Statistics::default();
+            *income = IncomeBreakdown::default();
+            *achievements = Achievements::from_unlocked(&[]);
+            *rng = GameRng::from_entropy();
+            save::clear(*slot);
+            spawn_hand(&mut commands, default_hand_name(&locale, 0), HandState::Filling, config.first_hand_fingers(), score.per_click());
+        }
+    }
+    *golden_spawner = GoldenClickSpawner(golden_click_delay(&mut rng));
+}
+
+// a game always has at least one hand, even if the save had none
+fn spawn_saved_hands(commands: &mut Commands, hands: Vec<SavedHand>, score: &Score, config: &GameConfig, locale: &Locale) {
+    if hands.is_empty() {
+        spawn_hand(commands, default_hand_name(locale, 0), HandState::Filling, config.first_hand_fingers(), score.per_click());
+    }
+    for (idx, hand) in hands.into_iter().enumerate() {
+        let name = if hand.name.is_empty() { default_hand_name(locale, idx) } else { hand.name };
+        let hand_entity = spawn_hand(commands, name, hand.state, hand.fingers, score.per_click());
+        commands.entity(hand_entity).insert((hand.auto_buy, HandStats { emitted: hand.emitted, idle: false }));
+    }
+}
+
+pub fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut saved_hands: ResMut<SavedHands>,
+    score: Res<Score>,
+    config: Res<GameConfig>,
+    locale: Res<Locale>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    spawn_particle_pool(&mut commands);
+
+    commands.insert_resource(Sounds {
+        click: Some(asset_server.load("sounds/click.ogg")),
+        purchase: Some(asset_server.load("sounds/purchase.ogg")),
+        cashout: Some(asset_server.load("sounds/cashout.ogg")),
+    });
+
+    // set up backdrop
+    commands.spawn(SpriteBundle {
+        texture: asset_server.load("backdrop.png"),
+        transform: Transform::from_xyz(0.0, 0.0, 0.0).with_scale(Vec3::splat(4.0)),
+        ..SpriteBundle::default()
+    });
+
+    spawn_saved_hands(&mut commands, std::mem::take(&mut saved_hands.0), &score, &config, &locale);
+}
+
+// the sound files aren't shipped with the game yet. each one that fails to
+// load is logged once and then skipped by play_sound.
+pub fn drop_failed_sounds_system(asset_server: Res<AssetServer>, mut sounds: ResMut<Sounds>) {
+    for sound in sounds.all_mut() {
+        if let Some(handle) = sound {
+            if asset_server.get_load_state(&*handle) == Some(LoadState::Failed) {
+                warn!("{:?} failed to load, playing without it", handle.path());
+                *sound = None;
+            }
+        }
+    }
+}
+
+// the save to play, before anything else. new games in an empty slot keep
+// the settings already in use.
+#[allow(clippy::too_many_arguments)]
+pub fn slot_picker_window(
+    mut contexts: EguiContexts,
+    mut commands: Commands,
+    mut summaries: ResMut<SlotSummaries>,
+    mut next_state: ResMut<NextState<State>>,
+    // the slot waiting on a second click to be deleted
+    mut confirm_delete: Local<Option<usize>>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+) {
+    let now = save::unix_now();
+    let mut picked = None;
+    egui::Window::new(locale.t("slots.title"))
+        .id("slots".into())
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .collapsible(false)
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Grid::new("slot_rows").num_columns(3).spacing([16.0, 8.0]).show(ui, |ui| {
+                for (slot, summary) in summaries.0.iter_mut().enumerate() {
+                    ui.strong(locale.tf("slots.slot", &[("number", &(slot + 1))]));
+                    ui.vertical(|ui| match summary {
+                        Some(summary) => {
+                            ui.label(locale.tf("slots.summary", &[
+                                ("play_time", &format_duration(summary.play_time)),
+                                ("buildings", &summary.buildings),
+                            ]));
+                            if let (Some(now), Some(saved_at)) = (now, summary.saved_at) {
+                                let ago = std::time::Duration::from_secs(now.saturating_sub(saved_at));
+                                ui.weak(locale.tf("slots.last_played", &[("ago", &format_duration(ago))]));
+                            }
+                        }
+                        None => {
+                            ui.weak(locale.t("slots.empty"));
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        if ui.button(locale.t(if summary.is_some() { "slots.play" } else { "slots.new" })).clicked() {
+                            picked = Some(slot);
+                        }
+                        if summary.is_none() {
+                            return;
+                        }
+                        if *confirm_delete != Some(slot) {
+                            if ui.button(locale.t("slots.delete")).clicked() {
+                                *confirm_delete = Some(slot);
+                            }
+                            return;
+                        }
+                        let really = egui::RichText::new(locale.t("slots.confirm_delete")).color(ui.visuals().error_fg_color);
+                        if ui.button(really).clicked() {
+                            save::clear(ActiveSlot(slot));
+                            *summary = None;
+                            *confirm_delete = None;
+                        }
+                        if ui.button(locale.t("slots.cancel")).clicked() {
+                            *confirm_delete = None;
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+        });
+    let Some(slot) = picked else {
+        return;
+    };
+    let data = match summaries.0[slot] {
+        Some(_) => save::load(ActiveSlot(slot)),
+        None => SaveData { settings: settings.clone(), ..SaveData::default() },
+    };
+    next_state.set(if data.seen_tutorial { State::Game } else { State::Welcome });
+    save::restore(&mut commands, ActiveSlot(slot), data);
+}
+
+// the tutorial pages are welcome.0 through welcome.N in the locale tables
+pub const WELCOME_PAGES : usize = 6;
+
+pub fn welcome_window(
+    mut contexts: EguiContexts,
+    mut next_state: ResMut<NextState<State>>,
+    mut message_index: Local<usize>,
+    mut score: ResMut<Score>,
+    locale: Res<Locale>,
+) {
+    egui::Window::new(locale.t("welcome.title"))
+        .id("welcome".into())
+        .show(contexts.ctx_mut(), |ui| {
+            let last = *message_index + 1 >= WELCOME_PAGES;
+            ui.label(locale.t(&format!("welcome.{}", *message_index)));
+            // nothing has been played yet, so the run's difficulty can still be picked
+            if last {
+                let mut difficulty = score.difficulty;
+                difficulty_picker(ui, &mut difficulty, &locale);
+                if difficulty != score.difficulty {
+                    score.difficulty = difficulty;
+                }
+            }
+            ui.horizontal(|ui| {
+                if ui.button(locale.t(if last { "welcome.start" } else { "welcome.next" })).clicked() {
+                    if last {
+                        next_state.set(State::Game);
+                    } else {
+                        *message_index += 1;
+                    }
+                }
+                if !last && ui.button(locale.t("welcome.skip")).clicked() {
+                    next_state.set(State::Game);
+                }
+            });
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn win_window(
+    mut contexts: EguiContexts,
+    mut score: ResMut<Score>,
+    statistics: Res<Statistics>,
+    settings: Res<Settings>,
+    mut next_state: ResMut<NextState<State>>,
+    mut new_game_events: EventWriter<NewGame>,
+    // the next game's, starting out as this one's
+    mut difficulty: Local<Option<Difficulty>>,
+    locale: Res<Locale>,
+) {
+    egui::Window::new(locale.t("win.title"))
+        .id("win".into())
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .collapsible(false)
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label(locale.t("win.message"));
+            ui.separator();
+            egui::Grid::new("win_stats").num_columns(2).show(ui, |ui| {
+                let rows = [
+                    ("win.play_time", format_duration(statistics.play_time)),
+                    ("win.lifetime_clicks", settings.format(statistics.lifetime_clicks)),
+                    // every cashout raises a building, and the first one comes free
+                    ("win.cashouts", score.buildings.saturating_sub(1).to_string()),
+                    ("win.peak_cps", settings.format(statistics.peak_cps.round() as u64)),
+                    ("win.difficulty", locale.t(score.difficulty.label_key())),
+                ];
+                for (key, value) in rows {
+                    ui.label(locale.t(key));
+                    ui.strong(value);
+                    ui.end_row();
+                }
+            });
+            ui.separator();
+            let difficulty = difficulty.get_or_insert(score.difficulty);
+            difficulty_picker(ui, difficulty, &locale);
+            ui.horizontal(|ui| {
+                if ui.button(locale.t("win.new_game")).clicked() {
+                    new_game_events.send(NewGame { difficulty: *difficulty });
+                    next_state.set(State::Game);
+                }
+                // sandbox runs never finish, so this doesn't come straight back here
+                if ui.button(locale.t("win.continue")).on_hover_text(locale.t("win.continue_hint")).clicked() {
+                    score.sandbox = true;
+                    next_state.set(State::Game);
+                }
+            });
+        });
+}
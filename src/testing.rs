@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+
+use crate::{
+    balance::BalanceConfig,
+    economy::{apply_purchase_system, Purchase, PurchaseApplied},
+    locale::Locale,
+    resources::{ClicksEmitted, EventLog, GameConfig, GameRng, IncomeBreakdown, LowPower, Score, Settings, Sounds, Statistics},
+    states::State,
+};
+
+// a headless app with the resources and events the game's systems lean on,
+// minus windows, rendering and audio. add the systems under test to it.
+pub fn test_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .init_asset::<Image>()
+        .add_state::<State>()
+        .add_event::<ClicksEmitted>()
+        .insert_resource(Score::default())
+        .insert_resource(Statistics::default())
+        .insert_resource(IncomeBreakdown::default())
+        .insert_resource(Settings::default())
+        .insert_resource(BalanceConfig::default())
+        .init_resource::<GameConfig>()
+        .insert_resource(GameRng::from_seed(0))
+        .init_resource::<LowPower>()
+        .init_resource::<EventLog>()
+        .init_resource::<Sounds>();
+    app
+}
+
+// a test app whose every update moves the clock exactly `step`. an app's
+// first update only starts its clock, so that one is run here and every
+// update the test makes counts for a whole step.
+pub fn manual_clock_app(step: Duration) -> App {
+    let mut app = test_app();
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(step));
+    // steps longer than a frame usually gets would be clamped otherwise
+    app.world.resource_mut::<Time<Virtual>>().set_max_delta(step);
+    app.update();
+    app
+}
+
+// a test app that takes purchases through the same system the shop uses
+pub fn purchase_app() -> App {
+    let mut app = test_app();
+    app.add_event::<Purchase>()
+        .add_event::<PurchaseApplied>()
+        .init_resource::<Locale>()
+        .add_systems(Update, apply_purchase_system);
+    app
+}
+
+// where a state change asked for by the last update is headed
+pub fn next_state(app: &App) -> Option<State> {
+    app.world.resource::<NextState<State>>().0.clone()
+}
@@ -0,0 +1,2451 @@
+use std::{collections::{BTreeMap, BTreeSet}, time::Duration};
+
+use bevy::{
+    asset::LoadState,
+    diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    ecs::system::SystemParam,
+    input::{gamepad::GamepadButtonChangedEvent, keyboard::KeyboardInput, mouse::{MouseButtonInput, MouseWheel}, touch::{TouchInput, TouchPhase}},
+    prelude::*,
+    window::CursorMoved,
+    winit::{UpdateMode, WinitSettings},
+};
+use bevy_egui::{egui::{self, collapsing_header::CollapsingState, Widget}, EguiContexts, EguiSettings};
+use bevy_particle_systems::ParticleCount;
+
+use crate::{
+    balance::BalanceConfig,
+    buildings::MINIMAP_SIZE,
+    components::{ClickerQuery, HandIncome, HandLabel, HandQuery, HandState},
+    economy::{
+        best_value, despawn_golden_click, golden_click_reward, with_synergy, Achievements, Balance, CPS_HISTORY_LEN, ClickReadyFingers, CpsHistory,
+        Difficulty, Economy, GoldenClick, MetaUpgrade, MultiplierRaised, Purchase, AUTOED_VALUE, COMBINED_VALUE, MANUAL_VALUE,
+    },
+    locale::{Language, Locale},
+    particles::{CursorBurstRequested, ParticleTheme, SparksRequested, PARTICLE_THEMES},
+    resources::{
+        format_duration, key_name, ClickRate, ClickSource, ClicksEmitted, Combo, DisplayMode, EventLog, FastForward, GameConfig,
+        Headlines, Hotkey, Income, IncomeBreakdown, LastInput, LowPower, MissingAssets, NumberFormat, Presentation, RESOLUTIONS, Score,
+        Settings, Statistics, Theme, Toasts, BINDABLE_KEYS, FAST_FORWARD_SPEED, MAX_FRAME_CAP, REQUIRED_IMAGES, TOAST_FADE_SECS,
+    },
+    save::{
+        export_string, import_string, CashoutUndo, GameImported, GameSnapshot, ImportError, SaveRequested, SaveStatus, StatsExport, UndoCashout,
+    },
+    states::{NewGame, State},
+};
+
+// the systems that turn what the player does into events, so the rest of
+// the game can run after them
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PlayerInput;
+
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(FrameTimeDiagnosticsPlugin)
+            .add_event::<FloatingTextRequested>()
+            .add_event::<ReminderAnswer>()
+            .insert_resource(Toasts::default())
+            .insert_resource(UiState::default())
+            .insert_resource(FocusedHand::default())
+            .init_resource::<AffordableItems>()
+            .init_resource::<MissingAssets>()
+            .init_resource::<Headlines>()
+            .init_resource::<LastInput>()
+            .init_resource::<LowPower>()
+            .add_systems(Update, (
+                ui_system,
+                hotkey_system,
+                gamepad_system,
+            ).in_set(PlayerInput).run_if(in_state(State::Game)))
+            .add_systems(Update, (
+                pause_overlay_system,
+                rebind_system.before(settings_window).before(help_window),
+                golden_click_ui_system,
+                undo_cashout_window,
+                fps_overlay_system,
+                fast_forward_system,
+                missing_assets_system,
+                headline_system,
+                reminder_schedule_system.before(reminder_system),
+                reminder_system,
+                headline_ticker_system.after(headline_system),
+                affordability_toast_system,
+                spawn_floating_text_system.after(ui_system),
+                floating_text_system,
+            ).run_if(in_state(State::Game)))
+            .add_systems(Update, (hint_progress_system, hint_system).chain().before(PlayerInput).run_if(in_state(State::Game)))
+            .add_systems(Update, (level_up_system, level_up_sparks_system).run_if(in_state(State::Game)))
+            // the windows focus mode hides
+            .add_systems(Update, (
+                settings_window,
+                statistics_window,
+                achievements_window,
+                influence_window,
+                advisor_window,
+                event_log_window,
+                help_window,
+                share_window,
+                performance_window,
+            ).run_if(in_state(State::Game)).run_if(unfocused))
+            .add_systems(Update, (apply_settings_system, toast_system))
+            .add_systems(PreUpdate, (track_input_system, low_power_system.after(track_input_system), multi_touch_system));
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(Last, frame_cap_system);
+    }
+}
+
+// fraction of win_score reached. the division happens in integer parts per
+// million first so neither side has to squeeze ~1e12 into an f32.
+pub fn win_progress(stored_clicks: u64, win_score: u64) -> f32 {
+    let ppm = stored_clicks.min(win_score) as u128 * 1_000_000 / win_score.max(1) as u128;
+    ppm as f32 / 1_000_000.0
+}
+
+pub fn win_eta(stored_clicks: u64, win_score: u64, clicks_per_second: f64) -> Option<Duration> {
+    if clicks_per_second <= 0.0 {
+        return None;
+    }
+    let remaining = win_score.saturating_sub(stored_clicks);
+    // a rate decaying towards zero after the clicking stops gives an eta too
+    // long for a Duration, which is as good as none
+    Duration::try_from_secs_f64(remaining as f64 / clicks_per_second).ok()
+}
+
+#[derive(Resource, Default)]
+pub struct UiState {
+    settings_open: bool,
+    statistics_open: bool,
+    achievements_open: bool,
+    influence_open: bool,
+    advisor_open: bool,
+    event_log_open: bool,
+    help_open: bool,
+    share_open: bool,
+    // the action waiting for a key in the help or settings window
+    rebinding: Option<Hotkey>,
+    // toggled with F3
+    fps_overlay: bool,
+    // what the hint is pointing at this frame
+    hint: Option<Hint>,
+    // the break reminder is up, until it's answered
+    reminder_open: bool,
+    // where touches past the first started this frame, in egui points
+    touches: Vec<egui::Pos2>,
+}
+
+// the next step for a player still learning the game
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Hint {
+    Click,
+    Finger,
+    Hand,
+    Combine,
+    Auto,
+    UpgradeFingers,
+    Cashout,
+}
+
+impl Hint {
+    pub fn label_key(self) -> &'static str {
+        match self {
+            Hint::Click => "hint.click",
+            Hint::Finger => "hint.finger",
+            Hint::Hand => "hint.hand",
+            Hint::Combine => "hint.combine",
+            Hint::Auto => "hint.auto",
+            Hint::UpgradeFingers => "hint.upgrade_fingers",
+            Hint::Cashout => "hint.cashout",
+        }
+    }
+}
+
+// how far along a player is, as far as hints care
+#[derive(Default)]
+pub struct Progress {
+    pub lifetime_clicks: u64,
+    pub total_fingers: u64,
+    pub hands: usize,
+    // hands past filling, so autoed ones count too
+    pub combined: usize,
+    pub autoed: usize,
+    pub finger_upgrades: u32,
+    pub buildings: u32,
+}
+
+// whether the step a hint suggests has been taken
+pub type HintDone = fn(&Progress) -> bool;
+
+// in the order a new player should meet them
+pub const HINT_RULES : [(Hint, HintDone); 7] = [
+    (Hint::Click, |p| p.lifetime_clicks > 0),
+    // the first finger comes free
+    (Hint::Finger, |p| p.total_fingers > 1),
+    (Hint::Hand, |p| p.hands > 1),
+    (Hint::Combine, |p| p.combined > 0),
+    (Hint::Auto, |p| p.autoed > 0),
+    (Hint::UpgradeFingers, |p| p.finger_upgrades > 0),
+    (Hint::Cashout, |p| p.buildings > 1),
+];
+
+// where in HINT_RULES the first step not taken yet is, past the `taken`
+// ones from earlier runs. None once they all are.
+pub fn next_hint(progress: &Progress, taken: usize) -> Option<usize> {
+    HINT_RULES.iter().enumerate().skip(taken).find(|(_, (_, done))| !done(progress)).map(|(step, _)| step)
+}
+
+// windows whose positions are saved, by egui id
+pub const PERSISTED_WINDOWS : [&str; 10] = [
+    "store", "settings", "statistics", "achievements", "influence", "advisor", "event_log", "help", "share", "performance",
+];
+
+// how much of a restored window must stay on screen
+pub const WINDOW_MIN_VISIBLE : f32 = 40.0;
+
+// hand entities don't survive a restart, so hands are keyed by their order
+pub fn hand_window_key(index: usize) -> String {
+    format!("hand:{}", index)
+}
+
+// windows as they were left last session
+#[derive(Resource, Default)]
+pub struct WindowLayout {
+    // top-left corners
+    pub positions: BTreeMap<String, (f32, f32)>,
+    pub collapsed: BTreeSet<String>,
+}
+
+pub fn persisted_window_ids(hand_ids: impl Iterator<Item = egui::Id>) -> impl Iterator<Item = (String, egui::Id)> {
+    PERSISTED_WINDOWS.iter()
+        .map(|key| (key.to_string(), egui::Id::new(*key)))
+        .chain(hand_ids.enumerate().map(|(idx, id)| (hand_window_key(idx), id)))
+}
+
+// where egui keeps a window's collapsed flag
+pub fn window_collapsing_id(window_id: egui::Id) -> egui::Id {
+    window_id.with("collapsing")
+}
+
+pub fn set_window_open(ctx: &egui::Context, window_id: egui::Id, open: bool) {
+    let mut state = CollapsingState::load_with_default_open(ctx, window_collapsing_id(window_id), true);
+    state.set_open(open);
+    state.store(ctx);
+}
+
+impl WindowLayout {
+    pub fn capture(ctx: &egui::Context, hand_ids: impl Iterator<Item = egui::Id>) -> BTreeMap<String, (f32, f32)> {
+        persisted_window_ids(hand_ids)
+            .filter_map(|(key, id)| ctx.memory(|m| m.area_rect(id)).map(|rect| (key, (rect.min.x, rect.min.y))))
+            .collect()
+    }
+
+    pub fn capture_collapsed(ctx: &egui::Context, hand_ids: impl Iterator<Item = egui::Id>) -> BTreeSet<String> {
+        persisted_window_ids(hand_ids)
+            .filter(|(_, id)| CollapsingState::load(ctx, window_collapsing_id(*id)).is_some_and(|state| !state.is_open()))
+            .map(|(key, _)| key)
+            .collect()
+    }
+
+    // egui only honors default_pos and default_open the first time a window
+    // shows, which is exactly when we want the saved layout to apply
+    fn restore<'a>(&self, window: egui::Window<'a>, key: &str, screen: egui::Rect) -> egui::Window<'a> {
+        let window = window.default_open(!self.collapsed.contains(key));
+        match self.positions.get(key) {
+            Some(&(x, y)) => window.default_pos(clamp_to_screen(egui::pos2(x, y), screen)),
+            None => window,
+        }
+    }
+}
+
+// pulls a window corner back onto the screen, e.g. after a resolution change
+pub fn clamp_to_screen(pos: egui::Pos2, screen: egui::Rect) -> egui::Pos2 {
+    egui::pos2(
+        pos.x.clamp(screen.min.x, (screen.max.x - WINDOW_MIN_VISIBLE).max(screen.min.x)),
+        pos.y.clamp(screen.min.y, (screen.max.y - WINDOW_MIN_VISIBLE).max(screen.min.y)),
+    )
+}
+
+pub fn performance_window(
+    mut contexts: EguiContexts,
+    mut history: ResMut<CpsHistory>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    layout: Res<WindowLayout>,
+) {
+    let ctx = contexts.ctx_mut();
+    let window = egui::Window::new(locale.t("performance.title"))
+        .id("performance".into())
+        .default_open(false);
+    layout.restore(window, "performance", ctx.screen_rect())
+        .show(ctx, |ui| {
+            let peak = history.samples.iter().copied().fold(0.0, f64::max);
+            ui.horizontal(|ui| {
+                ui.label(locale.tf("performance.peak", &[("cps", &settings.format(peak.round() as u64))]));
+                let label = if history.paused { "performance.resume" } else { "performance.pause" };
+                if ui.button(locale.t(label)).clicked() {
+                    history.paused = !history.paused;
+                }
+            });
+
+            let (rect, _) = ui.allocate_exact_size(egui::vec2(240.0, 80.0), egui::Sense::hover());
+            let painter = ui.painter_at(rect);
+            painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+            if peak <= 0.0 {
+                return;
+            }
+            let step = rect.width() / (CPS_HISTORY_LEN - 1) as f32;
+            // newest sample sits on the right edge
+            let offset = CPS_HISTORY_LEN - history.samples.len();
+            let points = history.samples.iter().enumerate().map(|(idx, rate)| {
+                egui::pos2(
+                    rect.left() + (offset + idx) as f32 * step,
+                    rect.bottom() - (rate / peak) as f32 * rect.height(),
+                )
+            }).collect();
+            painter.add(egui::Shape::line(points, egui::Stroke::new(1.5, ui.visuals().selection.bg_fill)));
+        });
+}
+
+#[derive(SystemParam)]
+pub struct UiEvents<'w> {
+    clicks: EventWriter<'w, ClicksEmitted>,
+    purchases: EventWriter<'w, Purchase>,
+    saves: EventWriter<'w, SaveRequested>,
+    floating_text: EventWriter<'w, FloatingTextRequested>,
+    cursor_bursts: EventWriter<'w, CursorBurstRequested>,
+}
+
+// the balance and the game config, which between them set every price and target
+#[derive(SystemParam)]
+pub struct Rules<'w> {
+    balance: Res<'w, BalanceConfig>,
+    config: Res<'w, GameConfig>,
+}
+
+// what the game is earning, measured and predicted
+#[derive(SystemParam)]
+pub struct Rates<'w> {
+    click_rate: Res<'w, ClickRate>,
+    income: Res<'w, Income>,
+}
+
+pub const FLOATING_TEXT_SECS : f32 = 1.0;
+// logical pixels per second, upward
+pub const FLOATING_TEXT_RISE : f32 = 40.0;
+// autoed hands clap on their own and would bury the screen otherwise
+pub const THROTTLED_FLOATING_TEXT_PER_SECOND : f32 = 2.0;
+
+#[derive(Event)]
+pub struct FloatingTextRequested {
+    text: String,
+    // logical pixels from the top left of the window
+    at: Vec2,
+    throttled: bool,
+}
+
+#[derive(Component)]
+pub struct FloatingText {
+    lifetime: Timer,
+    position: Vec2,
+    velocity: Vec2,
+}
+
+pub fn spawn_floating_text_system(
+    mut requests: EventReader<FloatingTextRequested>,
+    mut commands: Commands,
+    // seconds until another throttled text may spawn
+    mut throttle: Local<f32>,
+    time: Res<Time>,
+) {
+    *throttle -= time.delta_seconds();
+    for request in requests.read() {
+        if request.throttled {
+            if *throttle > 0.0 {
+                continue;
+            }
+            *throttle = 1.0 / THROTTLED_FLOATING_TEXT_PER_SECOND;
+        }
+        commands.spawn((
+            TextBundle::from_section(request.text.clone(), TextStyle {
+                font_size: 24.0,
+                color: Color::GOLD,
+                ..default()
+            }).with_style(Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(request.at.x),
+                top: Val::Px(request.at.y),
+                ..default()
+            }),
+            FloatingText {
+                lifetime: Timer::from_seconds(FLOATING_TEXT_SECS, TimerMode::Once),
+                position: request.at,
+                velocity: Vec2::new(0.0, -FLOATING_TEXT_RISE),
+            },
+        ));
+    }
+}
+
+pub fn floating_text_system(
+    mut commands: Commands,
+    mut texts: Query<(Entity, &mut FloatingText, &mut Style, &mut Text)>,
+    time: Res<Time>,
+) {
+    for (entity, mut floating, mut style, mut text) in texts.iter_mut() {
+        if floating.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let velocity = floating.velocity;
+        floating.position += velocity * time.delta_seconds();
+        style.left = Val::Px(floating.position.x);
+        style.top = Val::Px(floating.position.y);
+        let alpha = floating.lifetime.percent_left();
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(alpha);
+        }
+    }
+}
+
+// seconds per glow of a ready finger
+pub const READY_PULSE_PERIOD : f32 = 1.5;
+// how far the glow reaches toward the selection color, 0-1
+pub const READY_PULSE_STRENGTH : f32 = 0.35;
+
+// a ready finger's fill, eased back and forth toward the selection color
+fn ready_pulse_fill(visuals: &egui::Visuals, elapsed: f32) -> egui::Color32 {
+    let phase = (elapsed * std::f32::consts::TAU / READY_PULSE_PERIOD).sin() * 0.5 + 0.5;
+    let from = egui::Rgba::from(visuals.widgets.inactive.weak_bg_fill);
+    let to = egui::Rgba::from(visuals.selection.bg_fill);
+    egui::lerp(from..=to, phase * READY_PULSE_STRENGTH).into()
+}
+
+// logical pixels, along the bottom edge of a cooling finger's button
+pub const COOLDOWN_BAR_HEIGHT : f32 = 3.0;
+
+// a bar that fills across a cooling finger as it gets ready again. the ready
+// state is a plain (or pulsing) button, so the two never look alike.
+fn paint_cooldown(ui: &egui::Ui, rect: egui::Rect, progress: f32) {
+    let width = rect.width() * progress.clamp(0.0, 1.0);
+    let bar = egui::Rect::from_min_size(egui::pos2(rect.left(), rect.bottom() - COOLDOWN_BAR_HEIGHT), egui::vec2(width, COOLDOWN_BAR_HEIGHT));
+    ui.painter().rect_filled(bar, 0.0, ui.visuals().selection.stroke.color);
+}
+
+// a purchase the player can't make yet shows as a disabled button. the
+// tooltip explaining it shows either way.
+fn purchase_button(ui: &mut egui::Ui, label: impl Into<egui::WidgetText>, enabled: bool, tip: String) -> bool {
+    ui.add_enabled(enabled, egui::Button::new(label))
+        .on_hover_text(tip.clone())
+        .on_disabled_hover_text(tip)
+        .clicked()
+}
+
+// a purchase priced in clicks. out of reach, the label turns red and the
+// tooltip leads with how many more clicks it takes.
+fn cost_button(ui: &mut egui::Ui, label: String, cost: u64, stored: u64, tip: String, settings: &Settings, locale: &Locale) -> bool {
+    if stored >= cost {
+        return purchase_button(ui, label, true, tip);
+    }
+    let need = locale.tf("tip.need_more", &[("clicks", &settings.format(cost - stored))]);
+    let label = egui::RichText::new(label).color(ui.visuals().error_fg_color);
+    purchase_button(ui, label, false, format!("{}\n{}", need, tip))
+}
+
+// outlines whatever `add` draws when the hint is pointing at it
+fn hinted<R>(ui: &mut egui::Ui, pointed_at: bool, add: impl FnOnce(&mut egui::Ui) -> R) -> R {
+    let scope = ui.scope(add);
+    if pointed_at {
+        ui.painter().rect_stroke(scope.response.rect.expand(2.0), 3.0, egui::Stroke::new(2.0, ui.visuals().warn_fg_color));
+    }
+    scope.inner
+}
+
+// a shape and color ahead of each hand's title. the colors are from the
+// Okabe-Ito palette, which stays distinct under the common kinds of color
+// blindness, and the shapes tell the states apart without any color at all.
+pub fn hand_state_badge(state: HandState) -> (&'static str, egui::Color32) {
+    match state {
+        HandState::Filling => ("○", egui::Color32::GRAY),
+        HandState::Combined => ("◑", egui::Color32::from_rgb(0, 114, 178)),
+        HandState::Autoed => ("⏺", egui::Color32::from_rgb(0, 158, 115)),
+    }
+}
+
+fn hand_title(style: &egui::Style, state: HandState, title: String) -> egui::text::LayoutJob {
+    let (badge, color) = hand_state_badge(state);
+    let mut job = egui::text::LayoutJob::default();
+    for (text, color) in [(format!("{} ", badge), color), (title, style.visuals.text_color())] {
+        egui::RichText::new(text).heading().color(color).append_to(&mut job, style, egui::FontSelection::Default, egui::Align::Center);
+    }
+    job
+}
+
+// how many hands are at each step
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct HandTally {
+    pub filling: u32,
+    pub combined: u32,
+    pub autoed: u32,
+}
+
+impl HandTally {
+    pub fn count<'a>(states: impl IntoIterator<Item = &'a HandState>) -> Self {
+        states.into_iter().fold(HandTally::default(), |mut tally, state| {
+            match state {
+                HandState::Filling => tally.filling += 1,
+                HandState::Combined => tally.combined += 1,
+                HandState::Autoed => tally.autoed += 1,
+            }
+            tally
+        })
+    }
+}
+
+// shared by the welcome window and the new game confirmation
+pub fn difficulty_picker(ui: &mut egui::Ui, difficulty: &mut Difficulty, locale: &Locale) {
+    egui::ComboBox::from_label(locale.t("difficulty.label"))
+        .selected_text(locale.t(difficulty.label_key()))
+        .show_ui(ui, |ui| {
+            for choice in Difficulty::ALL {
+                ui.selectable_value(difficulty, choice, locale.t(choice.label_key()));
+            }
+        })
+        .response
+        .on_hover_text(locale.t("difficulty.hint"));
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn ui_system(
+    mut hands: HandQuery,
+    mut all_clickers: ClickerQuery,
+    mut contexts: EguiContexts,
+    score: Res<Score>,
+    settings: Res<Settings>,
+    mut events: UiEvents,
+    rates: Rates,
+    mut ui_state: ResMut<UiState>,
+    layout: Res<WindowLayout>,
+    save_status: Res<SaveStatus>,
+    time: Res<Time>,
+    mut combo: ResMut<Combo>,
+    rules: Rules,
+    focused: Res<FocusedHand>,
+    locale: Res<Locale>,
+) {
+    let Rules { balance, config } = rules;
+    let screen = contexts.ctx_mut().screen_rect();
+    let hint = ui_state.hint;
+    let touches = std::mem::take(&mut ui_state.touches);
+    let tally = HandTally::count(hands.iter().map(|(hand, ..)| hand));
+    let autoed = tally.autoed;
+    let style = contexts.ctx_mut().style();
+    for (idx, (hand, mut clap_timer, clickers, hand_entity, mut name, mut auto_buy, stats, hand_income)) in hands.iter_mut().enumerate() {
+        let HandIncome { synergy, per_clap, per_second } = *hand_income;
+        let is_focused = focused.hand == Some(hand_entity);
+        // focus mode shows only the focused hand. autoed ones keep clapping
+        // regardless, in auto_clap_system.
+        let hidden = focused.focus_mode && !is_focused;
+        let marker = match (is_focused, stats.idle) {
+            (true, _) => "▶ ",
+            (false, true) => "⚠ ",
+            (false, false) => "",
+        };
+        let rate = locale.tf("hand.per_second", &[("amount", &settings.format(score.boosted(per_second)))]);
+        let window = egui::Window::new(hand_title(&style, *hand, format!("{}{} ({})", marker, name.0, rate)))
+            .id(egui::Id::new(hand_entity));
+        if is_focused && focused.is_changed() {
+            contexts.ctx_mut().move_to_top(egui::LayerId::new(egui::Order::Middle, egui::Id::new(hand_entity)));
+        }
+        // everything this hand produced this frame, for one floating "+N"
+        let mut emitted = 0u64;
+        // where the pointer was on a Click, for a burst of sparks there
+        let mut clicked_at = None;
+        let shown = if hidden { None } else {
+            layout.restore(window, &hand_window_key(idx), screen).show(contexts.ctx_mut(), |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(locale.t("hand.name"));
+                    ui.text_edit_singleline(&mut name.0);
+                });
+                ui.label(locale.tf("hand.stats", &[("total", &settings.format(stats.emitted))]));
+                if stats.idle {
+                    ui.colored_label(ui.visuals().warn_fg_color, locale.t("hand.idle"));
+                }
+                if combo.hand == Some(hand_entity) && combo.bonus() > 1 {
+                    ui.colored_label(egui::Color32::GOLD, locale.tf("hand.combo", &[("bonus", &combo.bonus())]));
+                }
+
+                match *hand {
+                    HandState::Filling => {
+                        // buy finger
+                        let label = locale.tf("hand.buy_finger", &[("cost", &settings.format(score.finger_cost(&balance)))]);
+                        let tip = locale.tf("tip.finger", &[("multiplier", &settings.format(score.multiplier_with_fingers(&balance, 1)))]);
+                        let bought = hinted(ui, hint == Some(Hint::Finger), |ui| {
+                            cost_button(ui, label, score.finger_cost(&balance), score.stored_clicks, tip, &settings, &locale)
+                        });
+                        if bought {
+                            events.purchases.send(Purchase::Finger { hand: hand_entity, count: 1 });
+                        }
+                        // buy as many fingers as we can afford
+                        let (count, cost) = score.affordable_fingers(&balance, score.stored_clicks);
+                        let tip = locale.tf("tip.max_fingers", &[("multiplier", &settings.format(score.multiplier_with_fingers(&balance, count)))]);
+                        let label = if count > 0 {
+                            locale.tf("hand.buy_max_fingers", &[("count", &count), ("cost", &settings.format(cost))])
+                        } else {
+                            locale.t("hand.buy_max_fingers_unavailable")
+                        };
+                        if purchase_button(ui, label, count > 0, tip) {
+                            events.purchases.send(Purchase::Finger { hand: hand_entity, count });
+                        }
+                        // sell the newest finger, but never a hand's last one
+                        let label = if clickers.len() > 1 {
+                            locale.tf("hand.sell_finger", &[("refund", &settings.format(score.finger_refund(&balance)))])
+                        } else {
+                            locale.t("hand.sell_finger_unavailable")
+                        };
+                        if purchase_button(ui, label, clickers.len() > 1, locale.t("tip.sell_finger")) {
+                            events.purchases.send(Purchase::SellFinger { hand: hand_entity });
+                        }
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut auto_buy.enabled, locale.t("hand.auto_buy"));
+                            ui.add(egui::DragValue::new(&mut auto_buy.cap).prefix(locale.t("hand.auto_buy_cap")).clamp_range(1..=1000));
+                        });
+                        // make hand
+                        let label = locale.tf("hand.combine", &[("cost", &settings.format(score.combine_cost(&balance)))]);
+                        let combined = hinted(ui, hint == Some(Hint::Combine), |ui| {
+                            cost_button(ui, label, score.combine_cost(&balance), score.stored_clicks, locale.t("tip.combine"), &settings, &locale)
+                        });
+                        if combined {
+                            events.purchases.send(Purchase::Combine { hand: hand_entity });
+                        }
+                        
+                        hinted(ui, hint == Some(Hint::Click), |ui| egui::Grid::new("fingers").num_columns(5).striped(true).show(ui, |ui| {
+                            for (idx, clicker) in Iterator::enumerate(clickers.iter()) {
+                                // end row every 5
+                                if idx % 5 == 0 && idx != 0 {
+                                    ui.end_row();
+                                }
+
+                                let (state, mut timer) = all_clickers.get_mut(*clicker).unwrap();
+                                let per_click = with_synergy(state.per_click.saturating_mul(rates.income.multiplier), synergy).saturating_mul(combo.bonus());
+                                let label = locale.tf("hand.click", &[("amount", &settings.format(per_click))]);
+                                if timer.0.finished() {
+                                    let mut button = egui::widgets::Button::new(label);
+                                    if settings.ready_pulse && !settings.reduce_motion {
+                                        button = button.fill(ready_pulse_fill(ui.visuals(), time.elapsed_seconds()));
+                                    }
+                                    // the cooling button below has the same id, so a press
+                                    // that started on it still counts as held here
+                                    let response = button.ui(ui);
+                                    let held = settings.hold_to_click && response.is_pointer_button_down_on();
+                                    let touched = touches.iter().any(|at| touches_widget(ui, response.rect, *at));
+                                    if response.clicked() || held || touched {
+                                        timer.0.reset();
+                                        emitted = emitted.saturating_add(per_click);
+                                        clicked_at = response.interact_pointer_pos().or(clicked_at);
+                                        combo.hit(hand_entity);
+                                    }
+                                } else {
+                                    let response = egui::widgets::Button::new(label).selected(true).ui(ui);
+                                    paint_cooldown(ui, response.rect, timer.0.percent());
+                                }
+                            }
+                        }));
+                    }
+                    
+                    HandState::Combined => {
+                        // make hand auto
+                        let label = locale.tf("hand.make_auto", &[("cost", &settings.format(score.auto_cost(&balance)))]);
+                        let automated = hinted(ui, hint == Some(Hint::Auto), |ui| {
+                            cost_button(ui, label, score.auto_cost(&balance), score.stored_clicks, locale.t("tip.auto"), &settings, &locale)
+                        });
+                        if automated {
+                            events.purchases.send(Purchase::Auto { hand: hand_entity });
+                        }
+
+                        if clap_timer.0.finished() {
+                            if ui.button(locale.tf("hand.clap", &[("amount", &settings.format(per_clap))])).clicked() {
+                                clap_timer.0.reset();
+                                emitted = emitted.saturating_add(per_clap);
+                            }
+                        } else {
+                            egui::ProgressBar::new(clap_timer.0.percent()).desired_width(100.0).ui(ui);
+                        }
+                    }
+
+                    HandState::Autoed => {
+                        // already sent by auto_clap_system, counted here for the "+N"
+                        let claps = clap_timer.0.times_finished_this_tick() as u64;
+                        emitted = emitted.saturating_add(per_clap.saturating_mul(claps));
+
+                        egui::ProgressBar::new(clap_timer.0.percent()).desired_width(100.0).ui(ui);
+                    }
+                }
+            })
+        };
+        if emitted > 0 {
+            let source = match *hand {
+                HandState::Filling => ClickSource::Manual,
+                HandState::Combined => ClickSource::Clap,
+                HandState::Autoed => ClickSource::Auto,
+            };
+            if source != ClickSource::Auto {
+                events.clicks.send(ClicksEmitted { amount: emitted, source, hand: Some(hand_entity) });
+            }
+            if let (ClickSource::Manual, Some(at)) = (source, clicked_at) {
+                events.cursor_bursts.send(CursorBurstRequested { at: Vec2::new(at.x, at.y) * settings.ui_pixels_per_point() });
+            }
+            if let Some(shown) = shown {
+                // egui points to logical pixels
+                let top = shown.response.rect.center_top();
+                events.floating_text.send(FloatingTextRequested {
+                    text: format!("+{}", settings.format(emitted)),
+                    at: Vec2::new(top.x, top.y) * settings.ui_pixels_per_point(),
+                    throttled: matches!(*hand, HandState::Autoed),
+                });
+            }
+        }
+    }
+
+    let ready_claps = hands.iter()
+        .filter(|(hand, clap_timer, ..)| matches!(**hand, HandState::Combined) && clap_timer.0.finished())
+        .count();
+
+    if focused.focus_mode {
+        focus_strip(contexts.ctx_mut(), &score, rates.click_rate.rate(), &settings, &locale);
+        return;
+    }
+
+    let window = egui::Window::new(locale.t("store.title")).id("store".into());
+    layout.restore(window, "store", screen).show(contexts.ctx_mut(), |ui| {
+        ui.horizontal(|ui| {
+            for (label, open) in [("store.collapse_all", false), ("store.expand_all", true)] {
+                if ui.button(locale.t(label)).clicked() {
+                    for (_, _, _, hand_entity, ..) in hands.iter() {
+                        set_window_open(ui.ctx(), egui::Id::new(hand_entity), open);
+                    }
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui.button(locale.t("store.settings")).clicked() {
+                ui_state.settings_open = !ui_state.settings_open;
+            }
+            if ui.button(locale.t("store.statistics")).clicked() {
+                ui_state.statistics_open = !ui_state.statistics_open;
+            }
+            if ui.button(locale.t("store.achievements")).clicked() {
+                ui_state.achievements_open = !ui_state.achievements_open;
+            }
+            if ui.button(locale.t("store.influence")).clicked() {
+                ui_state.influence_open = !ui_state.influence_open;
+            }
+            if ui.button(locale.t("store.advisor")).clicked() {
+                ui_state.advisor_open = !ui_state.advisor_open;
+            }
+            if ui.button(locale.t("store.event_log")).clicked() {
+                ui_state.event_log_open = !ui_state.event_log_open;
+            }
+            if ui.button(locale.t("store.help")).clicked() {
+                ui_state.help_open = !ui_state.help_open;
+            }
+            if ui.button(locale.t("store.share")).clicked() {
+                ui_state.share_open = !ui_state.share_open;
+            }
+        });
+        ui.heading(locale.tf("store.net_worth", &[("worth", &settings.format(score.net_worth(rates.income.per_second)))]))
+            .on_hover_text(locale.t("store.net_worth_hint"));
+        ui.label(locale.tf("store.clicks", &[("clicks", &settings.format(score.stored_clicks))]));
+        ui.label(locale.tf("store.cps", &[("cps", &settings.format(rates.click_rate.rate().round() as u64))]));
+        if score.sandbox {
+            ui.label(locale.t("store.sandbox"));
+        } else {
+            let progress = win_progress(score.stored_clicks, config.win_score(&score, &balance));
+            egui::ProgressBar::new(progress).text(locale.tf("store.victory_progress", &[("percent", &settings.language.decimal(progress as f64 * 100.0, 1))])).ui(ui);
+            match win_eta(score.stored_clicks, config.win_score(&score, &balance), rates.click_rate.rate()) {
+                Some(eta) => ui.label(locale.tf("store.eta", &[("eta", &format_duration(eta))])),
+                None => ui.label("—"),
+            };
+        }
+        ui.label(locale.tf("store.fingers", &[("count", &settings.language.group_digits(score.total_fingers))]));
+        ui.label(locale.tf("store.hand_tally", &[("filling", &tally.filling), ("combined", &tally.combined), ("autoed", &tally.autoed)]));
+        ui.label(locale.tf("store.multiplier", &[("multiplier", &settings.format(score.multiplier(&balance)))]));
+        let next_multiplier = settings.language.group_digits(score.next_multiplier(&balance).unwrap_or(0));
+        ui.label(locale.tf("store.next_multiplier", &[("fingers", &next_multiplier)]));
+        if autoed > 0 {
+            let percent = settings.language.decimal(balance.synergy(autoed), 1);
+            ui.label(locale.tf("store.synergy", &[("percent", &percent), ("count", &autoed)]))
+                .on_hover_text(locale.t("store.synergy_hint"));
+        }
+        let idle_hands = hands.iter().filter(|(.., stats, _)| stats.idle).count();
+        if idle_hands > 0 {
+            ui.label(locale.tf("store.idle_hands", &[("count", &idle_hands)]));
+        }
+        // clap every ready combined hand at once; autoed hands clap themselves
+        if ready_claps > 0 {
+            if ui.button(locale.tf("store.clap_all", &[("count", &ready_claps)])).clicked() {
+                for (hand, mut clap_timer, _, hand_entity, .., hand_income) in hands.iter_mut() {
+                    if matches!(*hand, HandState::Combined) && clap_timer.0.finished() {
+                        clap_timer.0.reset();
+                        events.clicks.send(ClicksEmitted { amount: hand_income.per_clap, source: ClickSource::Clap, hand: Some(hand_entity) });
+                    }
+                }
+            }
+        } else {
+            ui.label(locale.t("store.clap_all_unavailable"));
+        }
+        // raise per_click on every finger, present and future
+        let upgrade_cost = score.finger_upgrade_cost(&balance);
+        let label = locale.tf("store.upgrade_fingers", &[("cost", &settings.format(upgrade_cost))]);
+        let upgraded = hinted(ui, hint == Some(Hint::UpgradeFingers), |ui| {
+            cost_button(ui, label, upgrade_cost, score.stored_clicks, locale.t("tip.upgrade_fingers"), &settings, &locale)
+        });
+        if upgraded {
+            events.purchases.send(Purchase::FingerUpgrade);
+        }
+        // shorten the clap period of every hand
+        if let Some(cost) = score.clap_speed_cost(&balance) {
+            // every purchasable tier has a faster one after it
+            let next_rate = 1.0 / balance.clap_periods[score.clap_speed as usize + 1];
+            let label = locale.tf("store.faster_clapping", &[("rate", &next_rate), ("cost", &settings.format(cost))]);
+            if cost_button(ui, label, cost, score.stored_clicks, locale.t("tip.faster_clapping"), &settings, &locale) {
+                events.purchases.send(Purchase::ClapSpeed);
+            }
+        } else {
+            ui.label(locale.tf("store.clapping_maxed", &[("rate", &score.claps_per_second(&balance))]));
+        }
+        // buy hand
+        let label = locale.tf("store.buy_hand", &[("cost", &settings.format(score.hand_cost(&balance)))]);
+        let bought = hinted(ui, hint == Some(Hint::Hand), |ui| {
+            cost_button(ui, label, score.hand_cost(&balance), score.stored_clicks, locale.t("tip.hand"), &settings, &locale)
+        });
+        if bought {
+            events.purchases.send(Purchase::Hand { count: 1 });
+        }
+        // as many hands as we can afford
+        let (count, cost) = score.affordable_hands(&balance, score.stored_clicks);
+        let label = if count > 0 {
+            locale.tf("store.buy_max_hands", &[("count", &count), ("cost", &settings.format(cost))])
+        } else {
+            locale.t("store.buy_max_hands_unavailable")
+        };
+        if purchase_button(ui, label, count > 0, locale.t("tip.max_hands")) {
+            events.purchases.send(Purchase::Hand { count });
+        }
+        if let Some(cashout) = score.cashout_cost(&balance) {
+            // what prestige buys, given the fingers it costs
+            if let Some(after) = score.cashed_out(&balance) {
+                ui.label(locale.tf("store.cashout_preview", &[
+                    ("current", &settings.format(score.multiplier(&balance))),
+                    ("after", &settings.format(after.multiplier(&balance))),
+                ]));
+            }
+            if let Some(influence) = score.cashout_influence(&balance) {
+                ui.label(locale.tf("store.cashout_influence", &[("influence", &settings.format(influence))]));
+            }
+            let label = locale.tf("store.cashout", &[("cost", &settings.format(cashout))]);
+            let after = score.cashed_out(&balance).map_or(0, |after| after.multiplier(&balance));
+            let tip = locale.tf("tip.cashout", &[("multiplier", &settings.format(after))]);
+            if hinted(ui, hint == Some(Hint::Cashout), |ui| cost_button(ui, label, cashout, score.stored_clicks, tip, &settings, &locale)) {
+                events.purchases.send(Purchase::Cashout);
+            }
+        } else {
+            ui.label(locale.tf("store.win", &[("score", &settings.format(config.win_score(&score, &balance)))]));
+        }
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button(locale.t("store.save_now")).clicked() {
+                events.saves.send(SaveRequested);
+            }
+            match save_status.last_saved {
+                Some(at) => ui.label(locale.tf("store.saved_ago", &[("ago", &format_duration(time.elapsed().saturating_sub(at)))])),
+                None => ui.label(locale.t("store.not_saved")),
+            };
+        });
+    });
+
+}
+
+// all focus mode keeps of the store
+fn focus_strip(ctx: &egui::Context, score: &Economy, cps: f64, settings: &Settings, locale: &Locale) {
+    egui::Area::new("focus_strip")
+        .anchor(egui::Align2::LEFT_TOP, egui::vec2(10.0, 10.0))
+        .interactable(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(locale.tf("store.clicks", &[("clicks", &settings.format(score.stored_clicks))]));
+                ui.label(locale.tf("store.cps", &[("cps", &settings.format(cps.round() as u64))]));
+                ui.weak(locale.t("focus.exit"));
+            });
+        });
+}
+
+// the whole game as text to copy out, or to paste another one in. egui
+// handles the clipboard: export copies, and pasting goes into the text box.
+pub fn share_window(
+    mut snapshot: GameSnapshot,
+    mut ui_state: ResMut<UiState>,
+    mut imports: EventWriter<GameImported>,
+    locale: Res<Locale>,
+    layout: Res<WindowLayout>,
+    mut text: Local<String>,
+    // the outcome of the last button press, and whether it went well
+    mut status: Local<Option<(String, bool)>>,
+) {
+    // a cheap handle, so the snapshot stays free for capturing
+    let ctx = snapshot.ctx_mut().clone();
+    let (mut export, mut import) = (false, false);
+    let window = egui::Window::new(locale.t("share.title"))
+        .id("share".into())
+        .open(&mut ui_state.share_open);
+    layout.restore(window, "share", ctx.screen_rect())
+        .show(&ctx, |ui| {
+            ui.label(locale.t("share.hint"));
+            ui.horizontal(|ui| {
+                export = ui.button(locale.t("share.export")).clicked();
+                import = ui.add_enabled(!text.trim().is_empty(), egui::Button::new(locale.t("share.import"))).clicked();
+            });
+            egui::ScrollArea::vertical().max_height(120.0).show(ui, |ui| {
+                ui.add(egui::TextEdit::multiline(&mut *text).desired_width(f32::INFINITY).code_editor());
+            });
+            if let Some((message, ok)) = &*status {
+                let color = if *ok { ui.visuals().text_color() } else { ui.visuals().error_fg_color };
+                ui.colored_label(color, message);
+            }
+        });
+    if export {
+        if let Some(exported) = export_string(&snapshot.capture()) {
+            ctx.output_mut(|output| output.copied_text = exported.clone());
+            *text = exported;
+            *status = Some((locale.t("share.copied"), true));
+        }
+    }
+    if import {
+        *status = Some(match import_string(&text) {
+            Ok(data) => {
+                imports.send(GameImported(Box::new(data)));
+                (locale.t("share.imported"), true)
+            }
+            Err(err) => {
+                let version = match err {
+                    ImportError::UnsupportedVersion(version) => version,
+                    _ => 0,
+                };
+                (locale.tf(err.label_key(), &[("version", &version)]), false)
+            }
+        });
+    }
+}
+
+// watches stored clicks for milestones. the first frame only takes a
+// reading, so loading a save doesn't replay every headline below it.
+pub fn headline_system(score: Res<Score>, mut headlines: ResMut<Headlines>, mut last: Local<Option<u64>>) {
+    if let Some(before) = last.replace(score.stored_clicks) {
+        if score.stored_clicks > before {
+            headlines.crossed(before, score.stored_clicks);
+        }
+    }
+}
+
+// works out the next step, for the store and hand windows to outline. the
+// steps taken are kept in the statistics, which outlive a cashout.
+pub fn hint_progress_system(
+    mut ui_state: ResMut<UiState>,
+    mut statistics: ResMut<Statistics>,
+    hands: Query<&HandState>,
+    score: Res<Score>,
+    settings: Res<Settings>,
+) {
+    let progress = Progress {
+        lifetime_clicks: statistics.lifetime_clicks,
+        total_fingers: score.total_fingers,
+        hands: hands.iter().count(),
+        combined: hands.iter().filter(|hand| !matches!(hand, HandState::Filling)).count(),
+        autoed: hands.iter().filter(|hand| matches!(hand, HandState::Autoed)).count(),
+        finger_upgrades: score.finger_upgrades,
+        buildings: score.buildings,
+    };
+    let step = next_hint(&progress, statistics.hint_steps);
+    let taken = step.unwrap_or(HINT_RULES.len());
+    if taken > statistics.hint_steps {
+        statistics.hint_steps = taken;
+    }
+    ui_state.hint = step.filter(|_| settings.hints).map(|step| HINT_RULES[step].0);
+}
+
+// shows the next step above the headlines. dismissing turns hints off for
+// good.
+pub fn hint_system(
+    mut contexts: EguiContexts,
+    ui_state: Res<UiState>,
+    mut settings: ResMut<Settings>,
+    locale: Res<Locale>,
+) {
+    let Some(hint) = ui_state.hint else {
+        return;
+    };
+    let mut dismissed = false;
+    egui::Area::new("hint")
+        .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -56.0))
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.colored_label(ui.visuals().warn_fg_color, locale.t(hint.label_key()));
+                    dismissed = ui.small_button("✕").on_hover_text(locale.t("hint.dismiss")).clicked();
+                });
+            });
+        });
+    if dismissed {
+        settings.hints = false;
+    }
+}
+
+// how long a snoozed break reminder waits before coming back
+pub const REMINDER_SNOOZE : Duration = Duration::from_secs(5 * 60);
+
+// whether `interval_mins` of play have passed since the last reminder
+pub fn reminder_due(play_time: Duration, last_reminder: Duration, interval_mins: u32) -> bool {
+    interval_mins > 0 && play_time.saturating_sub(last_reminder) >= Duration::from_secs(interval_mins as u64 * 60)
+}
+
+// the buttons on the break reminder
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub enum ReminderAnswer {
+    Snooze,
+    Dismiss,
+}
+
+// suggests a break every so often. it's counted as shown as soon as it
+// opens, so it only comes up once per interval however long it stays open.
+pub fn reminder_schedule_system(
+    mut ui_state: ResMut<UiState>,
+    mut statistics: ResMut<Statistics>,
+    mut answers: EventReader<ReminderAnswer>,
+    settings: Res<Settings>,
+) {
+    for answer in answers.read() {
+        if *answer == ReminderAnswer::Snooze {
+            // due again once the snooze has passed, rather than a whole interval
+            let interval = Duration::from_secs(settings.reminder_mins as u64 * 60);
+            statistics.last_reminder = (statistics.play_time + REMINDER_SNOOZE).saturating_sub(interval);
+        }
+        ui_state.reminder_open = false;
+    }
+    if !ui_state.reminder_open && reminder_due(statistics.play_time, statistics.last_reminder, settings.reminder_mins) {
+        statistics.last_reminder = statistics.play_time;
+        ui_state.reminder_open = true;
+    }
+}
+
+// the reminder reminder_schedule_system opened, with how long it's been
+pub fn reminder_system(
+    mut contexts: EguiContexts,
+    ui_state: Res<UiState>,
+    statistics: Res<Statistics>,
+    mut answers: EventWriter<ReminderAnswer>,
+    locale: Res<Locale>,
+) {
+    if !ui_state.reminder_open {
+        return;
+    }
+    egui::Window::new(locale.t("reminder.title"))
+        .id("reminder".into())
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .collapsible(false)
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label(locale.tf("reminder.message", &[("time", &format_duration(statistics.play_time))]));
+            ui.horizontal(|ui| {
+                if ui.button(locale.tf("reminder.snooze", &[("time", &format_duration(REMINDER_SNOOZE))])).clicked() {
+                    answers.send(ReminderAnswer::Snooze);
+                }
+                if ui.button(locale.t("reminder.dismiss")).clicked() {
+                    answers.send(ReminderAnswer::Dismiss);
+                }
+            });
+        });
+}
+
+// the oldest unread headline along the bottom of the screen, until dismissed
+pub fn headline_ticker_system(mut contexts: EguiContexts, mut headlines: ResMut<Headlines>, locale: Res<Locale>) {
+    let Some(key) = headlines.queue.front() else {
+        return;
+    };
+    let waiting = headlines.queue.len() - 1;
+    let mut dismissed = false;
+    egui::Area::new("headline_ticker")
+        .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -10.0))
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(locale.t(key)).strong());
+                    if waiting > 0 {
+                        ui.weak(locale.tf("headline.more", &[("count", &waiting)]));
+                    }
+                    dismissed = ui.small_button("✕").on_hover_text(locale.t("headline.dismiss")).clicked();
+                });
+            });
+        });
+    if dismissed {
+        headlines.queue.pop_front();
+    }
+}
+
+// a packaging mistake would otherwise just leave parts of the screen blank.
+// each failure is logged once, and the panel stays up for the session.
+pub fn missing_assets_system(
+    asset_server: Res<AssetServer>,
+    mut missing: ResMut<MissingAssets>,
+    mut contexts: EguiContexts,
+    locale: Res<Locale>,
+) {
+    for path in REQUIRED_IMAGES {
+        let handle: Handle<Image> = asset_server.load(path);
+        if asset_server.get_load_state(&handle) == Some(LoadState::Failed) && !missing.0.iter().any(|known| known == path) {
+            error!("failed to load {}, was it packaged with the game?", path);
+            missing.0.push(path.to_string());
+        }
+    }
+    if missing.0.is_empty() {
+        return;
+    }
+    egui::Window::new(locale.t("missing.title")).id("missing_assets".into()).show(contexts.ctx_mut(), |ui| {
+        ui.colored_label(ui.visuals().error_fg_color, locale.t("missing.hint"));
+        for path in &missing.0 {
+            ui.monospace(path);
+        }
+    });
+}
+
+// read-only numbers for chasing down slow frames, out of the way of the windows
+pub fn fps_overlay_system(
+    mut contexts: EguiContexts,
+    mut ui_state: ResMut<UiState>,
+    keys: Res<Input<KeyCode>>,
+    diagnostics: Res<DiagnosticsStore>,
+    particles: Query<&ParticleCount>,
+    locale: Res<Locale>,
+) {
+    if keys.just_pressed(KeyCode::F3) {
+        ui_state.fps_overlay = !ui_state.fps_overlay;
+    }
+    if !ui_state.fps_overlay {
+        return;
+    }
+    let smoothed = |id| diagnostics.get(id).and_then(|diagnostic| diagnostic.smoothed());
+    let fps = smoothed(FrameTimeDiagnosticsPlugin::FPS).map_or("—".to_string(), |fps| format!("{:.0}", fps));
+    let frame_time = smoothed(FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .map_or("—".to_string(), |ms| locale.language.decimal(ms, 1));
+    let count: usize = particles.iter().map(|count| count.0).sum();
+    // stacked above the minimap, which keeps the corner
+    egui::Area::new("fps_overlay")
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-10.0, -(MINIMAP_SIZE.y + 40.0)))
+        .interactable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label(locale.tf("overlay.fps", &[("fps", &fps)]));
+            ui.label(locale.tf("overlay.frame_time", &[("ms", &frame_time)]));
+            ui.label(locale.tf("overlay.particles", &[("count", &locale.language.group_digits(count as u64))]));
+        });
+}
+
+// a toggle at the top of the screen, which stays lit while it's on
+pub fn fast_forward_system(mut contexts: EguiContexts, mut fast_forward: ResMut<FastForward>, locale: Res<Locale>) {
+    egui::Area::new("fast_forward")
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 10.0))
+        .show(contexts.ctx_mut(), |ui| {
+            let label = locale.tf("fast_forward.toggle", &[("speed", &FAST_FORWARD_SPEED)]);
+            let mut enabled = fast_forward.enabled;
+            if ui.toggle_value(&mut enabled, label).on_hover_text(locale.t("fast_forward.hint")).changed() {
+                fast_forward.enabled = enabled;
+            }
+        });
+}
+
+// the filling hand finger hotkeys buy for: the focused one if it can take
+// fingers, otherwise the one with the fewest
+fn finger_target(focused: &FocusedHand, hands: &Query<(Entity, &HandState, &Children)>) -> Option<Entity> {
+    let filling = || hands.iter().filter(|(_, hand, _)| matches!(hand, HandState::Filling));
+    focused.hand
+        .filter(|focused| filling().any(|(hand, ..)| hand == *focused))
+        .or_else(|| filling().min_by_key(|(_, _, clickers)| clickers.len()).map(|(hand, ..)| hand))
+}
+
+// the keys bound in settings, plus Tab for focus mode and, while in it, the
+// arrows no action is bound to for cycling hands. buying goes through
+// Purchase like the store does, and nothing fires while typing into egui or
+// rebinding a key.
+#[allow(clippy::too_many_arguments)]
+pub fn hotkey_system(
+    keys: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
+    ui_state: Res<UiState>,
+    mut focused: ResMut<FocusedHand>,
+    hands: Query<(Entity, &HandState, &Children)>,
+    score: Res<Score>,
+    balance: Res<BalanceConfig>,
+    mut contexts: EguiContexts,
+    mut click_requests: EventWriter<ClickReadyFingers>,
+    mut purchases: EventWriter<Purchase>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+) {
+    if ui_state.rebinding.is_some() || contexts.ctx_mut().wants_keyboard_input() {
+        return;
+    }
+    let bindings = settings.keybindings;
+    let order: Vec<Entity> = hands.iter().map(|(hand, ..)| hand).collect();
+    if keys.just_pressed(KeyCode::Tab) {
+        focused.focus_mode = !focused.focus_mode;
+    }
+    if focused.focus_mode {
+        // a hand cashed out from under focus mode hands it to the first one
+        if !focused.hand.is_some_and(|hand| order.contains(&hand)) {
+            focused.hand = order.first().copied();
+        }
+        let free = |key| Hotkey::ALL.into_iter().all(|hotkey| bindings.get(hotkey) != key);
+        let pressed = |key| free(key) && keys.just_pressed(key);
+        let step = pressed(KeyCode::Right) as isize - pressed(KeyCode::Left) as isize;
+        if step != 0 {
+            focused.cycle(&order, step);
+        }
+    }
+    if keys.just_pressed(bindings.pause) {
+        if virtual_time.is_paused() {
+            virtual_time.unpause();
+        } else {
+            virtual_time.pause();
+        }
+    }
+    if keys.just_pressed(bindings.click) {
+        click_requests.send(ClickReadyFingers);
+    }
+    if keys.just_pressed(bindings.buy_hand) {
+        purchases.send(Purchase::Hand { count: 1 });
+    }
+    if keys.just_pressed(bindings.buy_finger) {
+        if let Some(hand) = finger_target(&focused, &hands) {
+            purchases.send(Purchase::Finger { hand, count: 1 });
+        }
+    }
+    if keys.just_pressed(bindings.smart_buy) {
+        let rate = score.finger_rate(&balance);
+        let target = finger_target(&focused, &hands);
+        let mut options = vec![
+            // a new hand only adds anything when there's nowhere left to put fingers
+            (Purchase::Hand { count: 1 }, score.hand_cost(&balance), if target.is_none() { rate * MANUAL_VALUE } else { 0.0 }),
+        ];
+        if let Some(hand) = target {
+            options.push((Purchase::Finger { hand, count: 1 }, score.finger_cost(&balance), rate * MANUAL_VALUE));
+        }
+        for (hand, state, clickers) in &hands {
+            let fingers = clickers.len() as f64 * rate;
+            match state {
+                HandState::Filling => {
+                    options.push((Purchase::Combine { hand }, score.combine_cost(&balance), fingers * (COMBINED_VALUE - MANUAL_VALUE)));
+                }
+                HandState::Combined => {
+                    options.push((Purchase::Auto { hand }, score.auto_cost(&balance), fingers * (AUTOED_VALUE - COMBINED_VALUE)));
+                }
+                HandState::Autoed => {}
+            }
+        }
+        if let Some(purchase) = best_value(&options, score.stored_clicks) {
+            purchases.send(purchase);
+        }
+    }
+}
+
+// the hand gamepad purchases go to, cycled with the shoulder buttons. focus
+// mode hides every window but its own and a strip of stats.
+#[derive(Resource, Default)]
+pub struct FocusedHand {
+    pub hand: Option<Entity>,
+    pub focus_mode: bool,
+}
+
+impl FocusedHand {
+    // steps through `order`, wrapping. with nothing focused, forward starts
+    // at the first hand and back at the last.
+    pub fn cycle(&mut self, order: &[Entity], step: isize) {
+        if order.is_empty() {
+            return;
+        }
+        let len = order.len() as isize;
+        let next = match self.hand.and_then(|hand| order.iter().position(|h| *h == hand)) {
+            Some(current) => (current as isize + step).rem_euclid(len),
+            None if step > 0 => 0,
+            None => len - 1,
+        };
+        self.hand = Some(order[next as usize]);
+    }
+}
+
+pub fn unfocused(focused: Res<FocusedHand>) -> bool {
+    !focused.focus_mode
+}
+
+// South clicks like Space, West buys a finger for the focused hand and
+// Start pauses. mouse and keyboard keep working alongside.
+pub fn gamepad_system(
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    hands: Query<Entity, With<HandLabel>>,
+    mut focused: ResMut<FocusedHand>,
+    mut click_requests: EventWriter<ClickReadyFingers>,
+    mut purchases: EventWriter<Purchase>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+) {
+    let pressed = |kind| gamepads.iter().any(|gamepad| buttons.just_pressed(GamepadButton::new(gamepad, kind)));
+    if pressed(GamepadButtonType::South) {
+        click_requests.send(ClickReadyFingers);
+    }
+    // bevy calls the bumpers LeftTrigger/RightTrigger
+    let step = pressed(GamepadButtonType::RightTrigger) as isize - pressed(GamepadButtonType::LeftTrigger) as isize;
+    if step != 0 {
+        let order: Vec<Entity> = hands.iter().collect();
+        focused.cycle(&order, step);
+    }
+    if pressed(GamepadButtonType::West) {
+        if let Some(hand) = focused.hand {
+            purchases.send(Purchase::Finger { hand, count: 1 });
+        }
+    }
+    if pressed(GamepadButtonType::Start) {
+        if virtual_time.is_paused() {
+            virtual_time.unpause();
+        } else {
+            virtual_time.pause();
+        }
+    }
+}
+
+pub fn pause_overlay_system(virtual_time: Res<Time<Virtual>>, mut contexts: EguiContexts, locale: Res<Locale>) {
+    if !virtual_time.is_paused() {
+        return;
+    }
+    egui::Area::new("paused")
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .interactable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.heading(locale.t("paused"));
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn golden_click_ui_system(
+    golden_clicks: Query<(Entity, &GoldenClick)>,
+    mut contexts: EguiContexts,
+    mut commands: Commands,
+    score: Res<Score>,
+    click_rate: Res<ClickRate>,
+    mut clicker_events: EventWriter<ClicksEmitted>,
+    balance: Res<BalanceConfig>,
+    locale: Res<Locale>,
+) {
+    let ctx = contexts.ctx_mut();
+    let screen = ctx.screen_rect();
+    for (entity, golden) in &golden_clicks {
+        let pos = screen.min + egui::vec2(golden.position.x * screen.width(), golden.position.y * screen.height());
+        egui::Area::new(egui::Id::new(entity))
+            .fixed_pos(pos)
+            .show(ctx, |ui| {
+                let button = egui::Button::new(egui::RichText::new(locale.t("golden_finger")).color(egui::Color32::BLACK))
+                    .fill(egui::Color32::GOLD);
+                if ui.add(button).clicked() {
+                    // the rate already includes the influence boost, which
+                    // applies again when the reward lands
+                    let unboosted = click_rate.rate() * 100.0 / score.meta_upgrades.income_percent() as f64;
+                    let reward = golden_click_reward(unboosted, score.multiplier(&balance));
+                    clicker_events.send(ClicksEmitted { amount: reward, source: ClickSource::Golden, hand: None });
+                    despawn_golden_click(&mut commands, entity);
+                }
+            });
+    }
+}
+
+// top and center, under the fast forward toggle, for as long as the last
+// cashout can be taken back
+pub fn undo_cashout_window(
+    mut contexts: EguiContexts,
+    undo: Res<CashoutUndo>,
+    mut undo_events: EventWriter<UndoCashout>,
+    locale: Res<Locale>,
+) {
+    let Some(remaining) = undo.remaining_secs() else {
+        return;
+    };
+    egui::Area::new("undo_cashout")
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 40.0))
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(locale.t("undo_cashout.message"));
+                    if ui.button(locale.tf("undo_cashout.button", &[("seconds", &remaining.ceil())])).clicked() {
+                        undo_events.send(UndoCashout);
+                    }
+                });
+            });
+        });
+}
+
+// how long the level up banner stays, the last TOAST_FADE_SECS of it fading
+pub const LEVEL_UP_BANNER_SECS : f32 = 3.0;
+// bursts spread round the view for each tier
+pub const LEVEL_UP_BURSTS : usize = 12;
+
+// a flourish of sparks over the whole view whenever buying fingers doubles
+// the multiplier. it stays quiet: the cashout fanfare belongs to cashouts.
+pub fn level_up_sparks_system(
+    mut raised_events: EventReader<MultiplierRaised>,
+    mut spark_events: EventWriter<SparksRequested>,
+    cameras: Query<(&Transform, &OrthographicProjection), With<Camera2d>>,
+    settings: Res<Settings>,
+) {
+    if raised_events.read().last().is_none() {
+        return;
+    }
+    let Ok((transform, projection)) = cameras.get_single() else {
+        return;
+    };
+    let center = transform.translation.truncate();
+    // a single calm burst in the middle under reduce motion
+    let bursts = if settings.reduce_motion { 1 } else { LEVEL_UP_BURSTS };
+    for index in 0..bursts {
+        let offset = if bursts == 1 {
+            Vec2::ZERO
+        } else {
+            let angle = index as f32 / bursts as f32 * std::f32::consts::TAU;
+            Vec2::from_angle(angle) * projection.area.half_size() * 0.7
+        };
+        let theme: ParticleTheme = PARTICLE_THEMES[index % PARTICLE_THEMES.len()];
+        spark_events.send(SparksRequested { position: (center + offset).extend(100.0), theme });
+    }
+}
+
+// a banner naming the new multiplier, alongside level_up_sparks_system
+pub fn level_up_system(
+    mut contexts: EguiContexts,
+    mut raised_events: EventReader<MultiplierRaised>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    time: Res<Time>,
+    mut banner: Local<Option<(u64, Timer)>>,
+) {
+    if let Some(MultiplierRaised { multiplier }) = raised_events.read().last() {
+        *banner = Some((*multiplier, Timer::from_seconds(LEVEL_UP_BANNER_SECS, TimerMode::Once)));
+    }
+
+    let Some((multiplier, timer)) = banner.as_mut() else {
+        return;
+    };
+    if timer.tick(time.delta()).finished() {
+        *banner = None;
+        return;
+    }
+    let opacity = (timer.remaining_secs() / TOAST_FADE_SECS).min(1.0);
+    let text = locale.tf("level_up.banner", &[("multiplier", &settings.format(*multiplier))]);
+    egui::Area::new("level_up")
+        // clear of the undo cashout prompt above it
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 90.0))
+        .interactable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Frame::popup(ui.style()).multiply_with_opacity(opacity).show(ui, |ui| {
+                ui.label(egui::RichText::new(text).heading().strong().color(ui.visuals().warn_fg_color.gamma_multiply(opacity)));
+            });
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn settings_window(
+    mut contexts: EguiContexts,
+    mut settings: ResMut<Settings>,
+    mut score: ResMut<Score>,
+    locale: Res<Locale>,
+    mut ui_state: ResMut<UiState>,
+    layout: Res<WindowLayout>,
+    mut new_game_events: EventWriter<NewGame>,
+    // 0 = idle, then one step per confirmation
+    mut new_game_stage: Local<u8>,
+    mut new_game_difficulty: Local<Difficulty>,
+) {
+    let ui_state = &mut *ui_state;
+    let ctx = contexts.ctx_mut();
+    let window = egui::Window::new(locale.t("settings.title"))
+        .id("settings".into())
+        .open(&mut ui_state.settings_open);
+    layout.restore(window, "settings", ctx.screen_rect())
+        .show(ctx, |ui| {
+            // only flag the resource as changed when a widget actually moved
+            let mut edited = settings.clone();
+            let mut abbreviated = edited.number_format == NumberFormat::Abbreviated;
+            let mut changed = ui.checkbox(&mut abbreviated, locale.t("settings.abbreviate")).changed();
+            edited.number_format = if abbreviated { NumberFormat::Abbreviated } else { NumberFormat::Commas };
+            changed |= ui.add(egui::Slider::new(&mut edited.master_volume, 0.0..=1.0).text(locale.t("settings.volume"))).changed();
+            changed |= ui.add(egui::Slider::new(&mut edited.particle_density, 0.1..=2.0).text(locale.t("settings.particle_density"))).changed();
+            changed |= ui.checkbox(&mut edited.reduce_motion, locale.t("settings.reduce_motion"))
+                .on_hover_text(locale.t("settings.reduce_motion_hint"))
+                .changed();
+            // reduce motion overrides these, so they're greyed out under it
+            let moving = !edited.reduce_motion;
+            ui.add_enabled_ui(moving, |ui| {
+                changed |= ui.checkbox(&mut edited.scaled_bursts, locale.t("settings.scaled_bursts"))
+                    .on_hover_text(locale.t("settings.scaled_bursts_hint"))
+                    .changed();
+            });
+            ui.horizontal(|ui| {
+                changed |= ui.checkbox(&mut edited.auto_quality, locale.t("settings.auto_quality"))
+                    .on_hover_text(locale.t("settings.auto_quality_hint"))
+                    .changed();
+                ui.add_enabled_ui(!edited.auto_quality, |ui| {
+                    changed |= ui.add(egui::Slider::new(&mut edited.quality, 0.1..=1.0).text(locale.t("settings.quality")))
+                        .on_hover_text(locale.t("settings.quality_hint"))
+                        .changed();
+                });
+            });
+            changed |= ui.checkbox(&mut edited.simple_particles, locale.t("settings.simple_particles"))
+                .on_hover_text(locale.t("settings.simple_particles_hint"))
+                .changed();
+            // a building has a single site to stride over in simple mode
+            ui.add_enabled_ui(!edited.simple_particles, |ui| {
+                changed |= ui.add(egui::Slider::new(&mut edited.emitter_stride, 1..=8).text(locale.t("settings.emitter_stride")))
+                    .on_hover_text(locale.t("settings.emitter_stride_hint"))
+                    .changed();
+            });
+            ui.add_enabled_ui(moving, |ui| ui.horizontal(|ui| {
+                changed |= ui.checkbox(&mut edited.screen_shake, locale.t("settings.screen_shake")).changed();
+                ui.add_enabled_ui(edited.screen_shake, |ui| {
+                    changed |= ui.add(egui::Slider::new(&mut edited.shake_intensity, 0.0..=1.0).text(locale.t("settings.shake_intensity"))).changed();
+                });
+            }));
+            changed |= ui.checkbox(&mut edited.hints, locale.t("settings.hints"))
+                .on_hover_text(locale.t("settings.hints_hint"))
+                .changed();
+            changed |= ui.checkbox(&mut edited.minimap, locale.t("settings.minimap"))
+                .on_hover_text(locale.t("settings.minimap_hint"))
+                .changed();
+            ui.add_enabled_ui(moving, |ui| {
+                changed |= ui.checkbox(&mut edited.ready_pulse, locale.t("settings.ready_pulse"))
+                    .on_hover_text(locale.t("settings.ready_pulse_hint"))
+                    .changed();
+            });
+            changed |= ui.checkbox(&mut edited.hold_to_click, locale.t("settings.hold_to_click"))
+                .on_hover_text(locale.t("settings.hold_to_click_hint"))
+                .changed();
+            changed |= ui.add(egui::Slider::new(&mut edited.ui_scale, 0.5..=2.0).text(locale.t("settings.ui_scale"))).changed();
+            changed |= ui.checkbox(&mut edited.large_ui, locale.t("settings.large_ui"))
+                .on_hover_text(locale.t("settings.large_ui_hint"))
+                .changed();
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_label(locale.t("settings.theme"))
+                    .selected_text(locale.t(edited.theme.label_key()))
+                    .show_ui(ui, |ui| {
+                        for theme in Theme::ALL {
+                            changed |= ui.selectable_value(&mut edited.theme, theme, locale.t(theme.label_key())).changed();
+                        }
+                    });
+                // the backdrop and sparks are drawn for the dark theme
+                if edited.theme == Theme::Light {
+                    ui.colored_label(ui.visuals().warn_fg_color, "⚠").on_hover_text(locale.t("settings.theme_light_hint"));
+                }
+            });
+            ui.horizontal(|ui| {
+                let mut custom = edited.accent.is_some();
+                if ui.checkbox(&mut custom, locale.t("settings.accent")).changed() {
+                    let [r, g, b, _] = ui.visuals().selection.bg_fill.to_array();
+                    edited.accent = custom.then_some([r, g, b]);
+                    changed = true;
+                }
+                if let Some(accent) = &mut edited.accent {
+                    changed |= ui.color_edit_button_srgb(accent).changed();
+                }
+            });
+            changed |= ui.add(egui::Slider::new(&mut edited.autosave_secs, 0..=600).text(locale.t("settings.autosave"))).changed();
+            let off = locale.t("settings.off");
+            changed |= ui.add(
+                egui::Slider::new(&mut edited.idle_timeout_mins, 0..=60)
+                    .text(locale.t("settings.idle_timeout"))
+                    .custom_formatter(|mins, _| if mins == 0.0 { off.clone() } else { format!("{:.0}", mins) }),
+            )
+                .on_hover_text(locale.t("settings.idle_timeout_hint"))
+                .changed();
+            changed |= ui.add(
+                egui::Slider::new(&mut edited.reminder_mins, 0..=180)
+                    .text(locale.t("settings.reminder"))
+                    .custom_formatter(|mins, _| if mins == 0.0 { off.clone() } else { format!("{:.0}", mins) }),
+            )
+                .on_hover_text(locale.t("settings.reminder_hint"))
+                .changed();
+            egui::ComboBox::from_label(locale.t("settings.language"))
+                .selected_text(edited.language.native_name())
+                .show_ui(ui, |ui| {
+                    for language in Language::ALL {
+                        changed |= ui.selectable_value(&mut edited.language, language, language.native_name()).changed();
+                    }
+                });
+            egui::ComboBox::from_label(locale.t("settings.display"))
+                .selected_text(locale.t(edited.display_mode.label_key()))
+                .show_ui(ui, |ui| {
+                    for mode in DisplayMode::ALL {
+                        changed |= ui.selectable_value(&mut edited.display_mode, mode, locale.t(mode.label_key())).changed();
+                    }
+                });
+            ui.add_enabled_ui(edited.display_mode == DisplayMode::Windowed, |ui| {
+                let (width, height) = edited.resolution;
+                egui::ComboBox::from_label(locale.t("settings.resolution"))
+                    .selected_text(format!("{}×{}", width, height))
+                    .show_ui(ui, |ui| {
+                        for (width, height) in RESOLUTIONS {
+                            let label = format!("{}×{}", width, height);
+                            changed |= ui.selectable_value(&mut edited.resolution, (width, height), label).changed();
+                        }
+                    });
+            });
+            egui::ComboBox::from_label(locale.t("settings.presentation"))
+                .selected_text(locale.t(edited.presentation.label_key()))
+                .show_ui(ui, |ui| {
+                    for presentation in Presentation::ALL {
+                        changed |= ui.selectable_value(&mut edited.presentation, presentation, locale.t(presentation.label_key())).changed();
+                    }
+                })
+                .response
+                .on_hover_text(locale.t("settings.presentation_hint"));
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                let off = locale.t("settings.off");
+                changed |= ui.add(
+                    egui::Slider::new(&mut edited.frame_cap, 0..=MAX_FRAME_CAP)
+                        .text(locale.t("settings.frame_cap"))
+                        .custom_formatter(|fps, _| if fps == 0.0 { off.clone() } else { format!("{:.0}", fps) }),
+                ).changed();
+            }
+            if changed {
+                *settings = edited;
+            }
+            egui::CollapsingHeader::new(locale.t("settings.controls")).show(ui, |ui| {
+                controls_grid(ui, &settings, &mut ui_state.rebinding, &locale);
+            });
+            // part of the run rather than a preference, so it lives in the score
+            let mut sandbox = score.sandbox;
+            if ui.checkbox(&mut sandbox, locale.t("settings.sandbox")).on_hover_text(locale.t("settings.sandbox_hint")).changed() {
+                score.sandbox = sandbox;
+            }
+
+            ui.separator();
+            match *new_game_stage {
+                0 => {
+                    if ui.button(locale.t("settings.new_game")).clicked() {
+                        *new_game_difficulty = score.difficulty;
+                        *new_game_stage = 1;
+                    }
+                }
+                1 => {
+                    ui.label(locale.t("settings.new_game_confirm"));
+                    difficulty_picker(ui, &mut new_game_difficulty, &locale);
+                    ui.horizontal(|ui| {
+                        if ui.button(locale.t("settings.new_game_yes")).clicked() {
+                            *new_game_stage = 2;
+                        }
+                        if ui.button(locale.t("settings.cancel")).clicked() {
+                            *new_game_stage = 0;
+                        }
+                    });
+                }
+                _ => {
+                    ui.colored_label(egui::Color32::RED, locale.t("settings.new_game_final"));
+                    ui.horizontal(|ui| {
+                        if ui.button(locale.t("settings.erase_everything")).clicked() {
+                            new_game_events.send(NewGame { difficulty: *new_game_difficulty });
+                            *new_game_stage = 0;
+                        }
+                        if ui.button(locale.t("settings.cancel")).clicked() {
+                            *new_game_stage = 0;
+                        }
+                    });
+                }
+            }
+        });
+}
+
+// the purchases worth a heads-up when they come within reach
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StoreItem {
+    Finger,
+    Hand,
+    Combine,
+    Auto,
+    Cashout,
+}
+
+impl StoreItem {
+    const ALL : [StoreItem; 5] = [StoreItem::Finger, StoreItem::Hand, StoreItem::Combine, StoreItem::Auto, StoreItem::Cashout];
+
+    fn name_key(self) -> &'static str {
+        match self {
+            StoreItem::Finger => "item.finger",
+            StoreItem::Hand => "item.hand",
+            StoreItem::Combine => "item.combine",
+            StoreItem::Auto => "item.auto",
+            StoreItem::Cashout => "item.cashout",
+        }
+    }
+}
+
+// indexed like StoreItem::ALL. combining and automating need a hand in the
+// right state to spend on, so they only count when there is one.
+pub fn affordable_items(score: &Economy, balance: &Balance, has_filling: bool, has_combined: bool) -> [bool; 5] {
+    let stored = score.stored_clicks;
+    StoreItem::ALL.map(|item| match item {
+        StoreItem::Finger => has_filling && stored >= score.finger_cost(balance),
+        StoreItem::Hand => stored >= score.hand_cost(balance),
+        StoreItem::Combine => has_filling && stored >= score.combine_cost(balance),
+        StoreItem::Auto => has_combined && stored >= score.auto_cost(balance),
+        StoreItem::Cashout => score.can_cash_out(balance),
+    })
+}
+
+// what was affordable last frame. None until the first check, so loading a
+// rich save doesn't announce everything at once.
+#[derive(Resource, Default)]
+pub struct AffordableItems(Option<[bool; 5]>);
+
+// toasts once each time an item goes from out of reach to affordable
+pub fn affordability_toast_system(
+    score: Res<Score>,
+    balance: Res<BalanceConfig>,
+    hands: Query<&HandState>,
+    mut last: ResMut<AffordableItems>,
+    mut toasts: ResMut<Toasts>,
+    locale: Res<Locale>,
+) {
+    let has_filling = hands.iter().any(|state| matches!(state, HandState::Filling));
+    let has_combined = hands.iter().any(|state| matches!(state, HandState::Combined));
+    let now = affordable_items(&score, &balance, has_filling, has_combined);
+    if let Some(before) = last.0.replace(now) {
+        for (idx, item) in StoreItem::ALL.iter().enumerate() {
+            if now[idx] && !before[idx] {
+                toasts.push(locale.tf("toast.affordable", &[("item", &locale.t(item.name_key()))]));
+            }
+        }
+    }
+}
+
+pub fn achievements_window(
+    mut contexts: EguiContexts,
+    achievements: Res<Achievements>,
+    locale: Res<Locale>,
+    mut ui_state: ResMut<UiState>,
+    layout: Res<WindowLayout>,
+) {
+    let ctx = contexts.ctx_mut();
+    let window = egui::Window::new(locale.t("achievements.title"))
+        .id("achievements".into())
+        .open(&mut ui_state.achievements_open);
+    layout.restore(window, "achievements", ctx.screen_rect())
+        .show(ctx, |ui| {
+            egui::Grid::new("achievements").num_columns(2).striped(true).show(ui, |ui| {
+                for achievement in achievements.0.iter() {
+                    if achievement.unlocked {
+                        ui.strong(format!("✔ {}", achievement.name(&locale)));
+                    } else {
+                        ui.weak(format!("🔒 {}", achievement.name(&locale)));
+                    }
+                    ui.label(achievement.description(&locale));
+                    ui.end_row();
+                }
+            });
+        });
+}
+
+// gives the action picked in a controls grid the next bindable key pressed.
+// a key that already did something else swaps, with a toast saying so.
+pub fn rebind_system(
+    mut settings: ResMut<Settings>,
+    mut ui_state: ResMut<UiState>,
+    mut toasts: ResMut<Toasts>,
+    keys: Res<Input<KeyCode>>,
+    locale: Res<Locale>,
+) {
+    let Some(hotkey) = ui_state.rebinding else {
+        return;
+    };
+    // closing the windows it was started from gives up too
+    if keys.just_pressed(KeyCode::Escape) || (!ui_state.help_open && !ui_state.settings_open) {
+        ui_state.rebinding = None;
+    } else if let Some(key) = BINDABLE_KEYS.into_iter().find(|key| keys.just_pressed(*key)) {
+        let old = settings.keybindings.get(hotkey);
+        if let Some(displaced) = settings.keybindings.set(hotkey, key) {
+            toasts.push(locale.tf("toast.rebound", &[
+                ("key", &key_name(key)),
+                ("action", &locale.t(&format!("hotkey.{}", hotkey.key()))),
+                ("other", &locale.t(&format!("hotkey.{}", displaced.key()))),
+                ("old", &key_name(old)),
+            ]));
+        }
+        ui_state.rebinding = None;
+    }
+}
+
+// every action and its key. clicking a key waits for a new one.
+fn controls_grid(ui: &mut egui::Ui, settings: &Settings, rebinding: &mut Option<Hotkey>, locale: &Locale) {
+    ui.weak(locale.t("help.rebind"));
+    egui::Grid::new("controls").num_columns(2).striped(true).show(ui, |ui| {
+        for hotkey in Hotkey::ALL {
+            ui.label(locale.t(&format!("hotkey.{}", hotkey.key())));
+            let label = match *rebinding {
+                Some(waiting) if waiting == hotkey => locale.t("help.press_key"),
+                _ => key_name(settings.keybindings.get(hotkey)),
+            };
+            if ui.button(label).clicked() {
+                *rebinding = Some(hotkey);
+            }
+            ui.end_row();
+        }
+        ui.label(locale.t("help.overlay"));
+        ui.label("F3");
+        ui.end_row();
+        ui.label(locale.t("help.focus"));
+        ui.label("Tab");
+        ui.end_row();
+    });
+    ui.weak(locale.t("help.reserved"));
+}
+
+// what the keys do, and where they get rebound
+pub fn help_window(
+    mut contexts: EguiContexts,
+    settings: Res<Settings>,
+    mut ui_state: ResMut<UiState>,
+    locale: Res<Locale>,
+    layout: Res<WindowLayout>,
+) {
+    let ui_state = &mut *ui_state;
+    let ctx = contexts.ctx_mut();
+    let window = egui::Window::new(locale.t("help.title"))
+        .id("help".into())
+        .open(&mut ui_state.help_open);
+    layout.restore(window, "help", ctx.screen_rect())
+        .show(ctx, |ui| {
+            ui.label(locale.t("help.intro"));
+            controls_grid(ui, &settings, &mut ui_state.rebinding, &locale);
+        });
+}
+
+// the permanent upgrade shop
+#[allow(clippy::too_many_arguments)]
+pub fn influence_window(
+    mut contexts: EguiContexts,
+    score: Res<Score>,
+    balance: Res<BalanceConfig>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    mut ui_state: ResMut<UiState>,
+    layout: Res<WindowLayout>,
+    mut purchases: EventWriter<Purchase>,
+) {
+    let ctx = contexts.ctx_mut();
+    let window = egui::Window::new(locale.t("influence.title"))
+        .id("influence".into())
+        .open(&mut ui_state.influence_open);
+    layout.restore(window, "influence", ctx.screen_rect())
+        .show(ctx, |ui| {
+            ui.label(locale.tf("influence.balance", &[("influence", &settings.format(score.influence))]));
+            ui.weak(locale.t("influence.hint"));
+            egui::Grid::new("influence").num_columns(3).striped(true).show(ui, |ui| {
+                for upgrade in MetaUpgrade::ALL {
+                    ui.strong(locale.t(&format!("influence.{}.name", upgrade.key())))
+                        .on_hover_text(locale.t(&format!("influence.{}.description", upgrade.key())));
+                    ui.label(locale.tf("influence.level", &[("level", &score.meta_upgrades.level(upgrade))]));
+                    let cost = score.meta_upgrade_cost(&balance, upgrade);
+                    let buy = egui::Button::new(locale.tf("influence.buy", &[("cost", &settings.format(cost))]));
+                    if ui.add_enabled(score.influence >= cost, buy).clicked() {
+                        purchases.send(Purchase::Meta(upgrade));
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+}
+
+// whether to grind for the next multiplier tier or cash out, on the way to
+// the win score at the income the hands are making now
+#[allow(clippy::too_many_arguments)]
+pub fn advisor_window(
+    mut contexts: EguiContexts,
+    score: Res<Score>,
+    rules: Rules,
+    income: Res<Income>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    mut ui_state: ResMut<UiState>,
+    layout: Res<WindowLayout>,
+) {
+    // pricing the next tier walks every finger to it, so only when shown
+    if !ui_state.advisor_open {
+        return;
+    }
+    let Rules { balance, config } = rules;
+    let goal = config.win_score(&score, &balance);
+    let advice = score.prestige_advice(&balance, score.boosted(income.per_second) as f64, goal);
+    let secs = |secs: f64| format_duration(Duration::try_from_secs_f64(secs).unwrap_or(Duration::MAX));
+    let ctx = contexts.ctx_mut();
+    let window = egui::Window::new(locale.t("advisor.title"))
+        .id("advisor".into())
+        .open(&mut ui_state.advisor_open);
+    layout.restore(window, "advisor", ctx.screen_rect())
+        .show(ctx, |ui| {
+            let Some(advice) = advice else {
+                ui.label(locale.t("advisor.unavailable"));
+                return;
+            };
+            let recommendation = locale.t(if advice.cash_out() { "advisor.cash_out" } else { "advisor.grind" });
+            ui.heading(locale.tf("advisor.recommendation", &[("advice", &recommendation)]));
+            ui.label(locale.tf("advisor.goal", &[("goal", &settings.format(goal))]));
+            egui::Grid::new("advisor").num_columns(3).striped(true).show(ui, |ui| {
+                ui.strong(locale.t("advisor.next_tier"));
+                ui.label(locale.tf("advisor.tier_plan", &[
+                    ("fingers", &settings.format(advice.tier_fingers)),
+                    ("cost", &settings.format(advice.tier_cost)),
+                    ("multiplier", &settings.format(advice.tier_multiplier)),
+                ]));
+                ui.label(locale.tf("advisor.time_to_goal", &[("time", &secs(advice.grind_secs))]));
+                ui.end_row();
+                ui.strong(locale.t("advisor.cashout"));
+                ui.label(locale.tf("advisor.cashout_plan", &[
+                    ("cost", &settings.format(advice.cashout_cost)),
+                    ("multiplier", &settings.format(advice.cashout_multiplier)),
+                ]));
+                ui.label(locale.tf("advisor.time_to_goal", &[("time", &secs(advice.cashout_secs))]));
+                ui.end_row();
+            });
+            ui.weak(locale.t("advisor.assumption"));
+        });
+}
+
+// newest at the bottom, followed as entries come in unless scrolled back
+pub fn event_log_window(
+    mut contexts: EguiContexts,
+    log: Res<EventLog>,
+    locale: Res<Locale>,
+    mut ui_state: ResMut<UiState>,
+    layout: Res<WindowLayout>,
+) {
+    let ctx = contexts.ctx_mut();
+    let window = egui::Window::new(locale.t("event_log.title"))
+        .id("event_log".into())
+        .open(&mut ui_state.event_log_open);
+    layout.restore(window, "event_log", ctx.screen_rect())
+        .show(ctx, |ui| {
+            if log.0.is_empty() {
+                ui.weak(locale.t("event_log.empty"));
+                return;
+            }
+            egui::ScrollArea::vertical().max_height(300.0).stick_to_bottom(true).show(ui, |ui| {
+                for (at, entry) in &log.0 {
+                    ui.horizontal(|ui| {
+                        ui.weak(format_duration(*at));
+                        ui.label(entry);
+                    });
+                }
+            });
+        });
+}
+
+pub fn toast_system(
+    mut contexts: EguiContexts,
+    mut toasts: ResMut<Toasts>,
+    time: Res<Time>,
+) {
+    for (_, timer) in toasts.0.iter_mut() {
+        timer.tick(time.delta());
+    }
+    toasts.0.retain(|(_, timer)| !timer.finished());
+    if toasts.0.is_empty() {
+        return;
+    }
+
+    let ctx = contexts.ctx_mut();
+    egui::Area::new("toasts")
+        .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-10.0, 10.0))
+        .interactable(false)
+        .show(ctx, |ui| {
+            for (message, timer) in toasts.0.iter() {
+                let opacity = (timer.remaining_secs() / TOAST_FADE_SECS).min(1.0);
+                egui::Frame::popup(ui.style()).multiply_with_opacity(opacity).show(ui, |ui| {
+                    ui.colored_label(ui.visuals().text_color().gamma_multiply(opacity), message);
+                });
+            }
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn statistics_window(
+    mut contexts: EguiContexts,
+    statistics: Res<Statistics>,
+    score: Res<Score>,
+    income: Res<IncomeBreakdown>,
+    cps_history: Res<CpsHistory>,
+    balance: Res<BalanceConfig>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    mut ui_state: ResMut<UiState>,
+    mut toasts: ResMut<Toasts>,
+    layout: Res<WindowLayout>,
+) {
+    let ctx = contexts.ctx_mut();
+    let window = egui::Window::new(locale.t("statistics.title"))
+        .id("statistics".into())
+        .open(&mut ui_state.statistics_open);
+    layout.restore(window, "statistics", ctx.screen_rect())
+        .show(ctx, |ui| {
+            ui.label(locale.tf("statistics.lifetime_clicks", &[("clicks", &settings.format(statistics.lifetime_clicks))]));
+            ui.label(locale.tf("statistics.play_time", &[("time", &format_duration(statistics.play_time))]));
+            ui.label(locale.tf("statistics.difficulty", &[("difficulty", &locale.t(score.difficulty.label_key()))]));
+            ui.separator();
+            ui.strong(locale.t("statistics.income"));
+            egui::Grid::new("income").num_columns(4).striped(true).show(ui, |ui| {
+                ui.label(locale.t("statistics.source"));
+                ui.label(locale.t("statistics.total"));
+                ui.label(locale.t("statistics.share"));
+                ui.label(locale.t("statistics.rate"));
+                ui.end_row();
+                for source in ClickSource::ALL {
+                    ui.label(locale.t(source.label_key()));
+                    ui.label(settings.format(income.total(source)));
+                    ui.label(format!("{}%", settings.language.decimal(income.share(source) as f64 * 100.0, 1)));
+                    ui.label(format!("{}/s", settings.format(income.rate(source).round() as u64)));
+                    ui.end_row();
+                }
+            });
+            ui.separator();
+            // for spreadsheets, through the clipboard so it works on the web too
+            ui.horizontal(|ui| {
+                let json = ui.button(locale.t("statistics.copy_json")).clicked();
+                let csv = ui.button(locale.t("statistics.copy_csv")).clicked();
+                if !json && !csv {
+                    return;
+                }
+                let export = StatsExport::new(&statistics, &score, &balance, &income, &cps_history);
+                let text = if json { export.to_json() } else { Some(export.to_csv()) };
+                if let Some(text) = text {
+                    ui.output_mut(|output| output.copied_text = text);
+                    toasts.push(locale.t("toast.stats_copied"));
+                }
+            });
+        });
+}
+
+// egui's visuals for a theme. the accent takes over wherever egui would use
+// its blue: selections, progress bars and pressed or hovered buttons.
+pub fn theme_visuals(theme: Theme, accent: Option<[u8; 3]>) -> egui::Visuals {
+    let mut visuals = match theme {
+        Theme::Dark => egui::Visuals::dark(),
+        Theme::Light => egui::Visuals::light(),
+    };
+    if let Some([r, g, b]) = accent {
+        let accent = egui::Color32::from_rgb(r, g, b);
+        visuals.selection.bg_fill = accent;
+        visuals.hyperlink_color = accent;
+        visuals.widgets.hovered.bg_stroke.color = accent;
+        visuals.widgets.active.bg_fill = accent;
+        visuals.widgets.active.weak_bg_fill = accent;
+    }
+    visuals
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn apply_settings_system(
+    settings: Res<Settings>,
+    mut global_volume: ResMut<GlobalVolume>,
+    mut egui_settings: ResMut<EguiSettings>,
+    mut contexts: EguiContexts,
+    mut windows: Query<&mut Window, With<bevy::window::PrimaryWindow>>,
+    mut locale: ResMut<Locale>,
+    // so other settings changing doesn't undo a window resized by hand
+    mut applied_display: Local<Option<(DisplayMode, (u32, u32))>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    *global_volume = GlobalVolume::new(settings.master_volume);
+    // bevy_egui multiplies this into the context's pixels_per_point
+    egui_settings.scale_factor = settings.ui_pixels_per_point() as f64;
+    contexts.ctx_mut().set_visuals(theme_visuals(settings.theme, settings.accent));
+    contexts.ctx_mut().style_mut(|style| style.spacing = ui_spacing(settings.large_ui));
+    if locale.language != settings.language {
+        *locale = Locale::new(settings.language);
+    }
+    let Ok(mut window) = windows.get_single_mut() else {
+        return;
+    };
+    let present_mode = settings.presentation.present_mode();
+    if window.present_mode != present_mode {
+        window.present_mode = present_mode;
+    }
+    // egui windows are constrained to the screen, so shrinking the window
+    // pulls any that would be left outside back into view
+    let display = (settings.display_mode, settings.resolution);
+    if *applied_display == Some(display) {
+        return;
+    }
+    window.mode = settings.display_mode.window_mode();
+    if settings.display_mode == DisplayMode::Windowed {
+        let (width, height) = settings.resolution;
+        window.resolution.set(width as f32, height as f32);
+    }
+    *applied_display = Some(display);
+}
+
+// in low power mode the app sleeps until input arrives or this much time
+// has passed, and the frame cap drops to match
+pub const LOW_POWER_WAIT : Duration = Duration::from_millis(200);
+pub const LOW_POWER_FRAME_CAP : u32 = 5;
+
+// egui's own spacing, or roomier buttons that are easier to hit with a finger
+pub fn ui_spacing(large_ui: bool) -> egui::style::Spacing {
+    let spacing = egui::style::Spacing::default();
+    if !large_ui {
+        return spacing;
+    }
+    egui::style::Spacing {
+        item_spacing: spacing.item_spacing * 1.5,
+        button_padding: spacing.button_padding * 2.0,
+        interact_size: egui::vec2(spacing.interact_size.x, spacing.interact_size.y * 1.5),
+        ..spacing
+    }
+}
+
+// bevy_egui drives egui's one pointer with the first touch down, the same
+// way the mouse would. every touch that starts while it's held is returned
+// here instead, in egui points, so mashing several fingers at once counts
+// each of them.
+pub fn secondary_touches<'a>(pointer: &mut Option<u64>, touches: impl IntoIterator<Item = &'a TouchInput>, pixels_per_point: f32) -> Vec<egui::Pos2> {
+    let mut started = Vec::new();
+    for touch in touches {
+        match touch.phase {
+            TouchPhase::Started if pointer.is_none() => *pointer = Some(touch.id),
+            TouchPhase::Started => {
+                let at = touch.position / pixels_per_point;
+                started.push(egui::pos2(at.x, at.y));
+            }
+            TouchPhase::Ended | TouchPhase::Canceled if *pointer == Some(touch.id) => *pointer = None,
+            _ => {}
+        }
+    }
+    started
+}
+
+pub fn multi_touch_system(
+    mut touch_events: EventReader<TouchInput>,
+    mut ui_state: ResMut<UiState>,
+    egui_settings: Res<EguiSettings>,
+    mut pointer: Local<Option<u64>>,
+) {
+    ui_state.touches = secondary_touches(&mut pointer, touch_events.read(), egui_settings.scale_factor as f32);
+}
+
+// whether a touch at `at` lands on the widget at `rect`, rather than on
+// something covering it or a part scrolled out of view
+fn touches_widget(ui: &egui::Ui, rect: egui::Rect, at: egui::Pos2) -> bool {
+    rect.contains(at) && ui.clip_rect().contains(at) && ui.ctx().layer_id_at(at) == Some(ui.layer_id())
+}
+
+// egui gets its input from the same events, so this covers the ui too
+#[allow(clippy::too_many_arguments)]
+pub fn track_input_system(
+    mut keyboard: EventReader<KeyboardInput>,
+    mut mouse_buttons: EventReader<MouseButtonInput>,
+    mut cursor: EventReader<CursorMoved>,
+    mut wheel: EventReader<MouseWheel>,
+    mut touches: EventReader<TouchInput>,
+    mut gamepad: EventReader<GamepadButtonChangedEvent>,
+    mut last_input: ResMut<LastInput>,
+    real_time: Res<Time<Real>>,
+) {
+    // every reader is drained, so none of them pile up
+    let inputs = keyboard.read().count()
+        + mouse_buttons.read().count()
+        + cursor.read().count()
+        + wheel.read().count()
+        + touches.read().count()
+        + gamepad.read().count();
+    if inputs > 0 {
+        last_input.0 = real_time.elapsed();
+    }
+}
+
+// drops to low power after the idle timeout. timers keep running on the
+// longer frames, so autoed hands produce just as much.
+pub fn low_power_system(
+    settings: Res<Settings>,
+    last_input: Res<LastInput>,
+    real_time: Res<Time<Real>>,
+    mut low_power: ResMut<LowPower>,
+    mut winit: ResMut<WinitSettings>,
+) {
+    let timeout = Duration::from_secs(settings.idle_timeout_mins as u64 * 60);
+    let idle = settings.idle_timeout_mins > 0 && real_time.elapsed().saturating_sub(last_input.0) >= timeout;
+    if low_power.0 == idle {
+        return;
+    }
+    low_power.0 = idle;
+    if idle {
+        winit.focused_mode = UpdateMode::ReactiveLowPower { wait: LOW_POWER_WAIT };
+        winit.unfocused_mode = UpdateMode::ReactiveLowPower { wait: LOW_POWER_WAIT };
+    } else {
+        *winit = WinitSettings::default();
+    }
+}
+
+// sleeps off whatever is left of the frame's share of a second. runs last
+// so the time spent on the frame itself counts.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn frame_cap_system(settings: Res<Settings>, low_power: Res<LowPower>, mut frame_started: Local<Option<std::time::Instant>>) {
+    let now = std::time::Instant::now();
+    let cap = if low_power.0 { LOW_POWER_FRAME_CAP } else { settings.frame_cap };
+    if cap > 0 {
+        if let Some(started) = *frame_started {
+            let budget = Duration::from_secs_f64(1.0 / cap as f64);
+            if let Some(left) = budget.checked_sub(now - started) {
+                std::thread::sleep(left);
+            }
+        }
+    }
+    *frame_started = Some(std::time::Instant::now());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{economy::WIN_SCORE, resources::LARGE_UI_SCALE};
+
+    #[test]
+    fn level_ups_start_every_burst() {
+        use crate::particles::{burst_system, spawn_particle_pool, AutoQuality, PlayingEmitter};
+        let mut app = crate::testing::test_app();
+        app.add_event::<MultiplierRaised>()
+            .add_event::<SparksRequested>()
+            .init_resource::<AutoQuality>()
+            .add_systems(Startup, |mut commands: Commands| {
+                spawn_particle_pool(&mut commands);
+                commands.spawn((Camera2d::default(), Transform::default(), OrthographicProjection::default()));
+            })
+            .add_systems(Update, (level_up_sparks_system, burst_system).chain());
+        app.update();
+        app.world.send_event(MultiplierRaised { multiplier: 2 });
+        app.update();
+
+        let playing = app.world.query_filtered::<(), PlayingEmitter>().iter(&app.world).count();
+        assert_eq!(playing, LEVEL_UP_BURSTS);
+    }
+
+    #[test]
+    fn idling_drops_to_low_power_until_the_next_input() {
+        let mut app = crate::testing::manual_clock_app(Duration::from_secs(30));
+        app.add_event::<KeyboardInput>()
+            .add_event::<MouseButtonInput>()
+            .add_event::<CursorMoved>()
+            .add_event::<MouseWheel>()
+            .add_event::<TouchInput>()
+            .add_event::<GamepadButtonChangedEvent>()
+            .init_resource::<LastInput>()
+            .init_resource::<WinitSettings>()
+            .add_systems(PreUpdate, (track_input_system, low_power_system.after(track_input_system)));
+        app.world.resource_mut::<Settings>().idle_timeout_mins = 1;
+        let low_power = |app: &App| app.world.resource::<LowPower>().0;
+        app.update();
+        assert!(!low_power(&app));
+        app.update();
+        assert!(low_power(&app));
+        assert!(matches!(app.world.resource::<WinitSettings>().focused_mode, UpdateMode::ReactiveLowPower { .. }));
+
+        app.world.send_event(KeyboardInput {
+            scan_code: 0,
+            key_code: Some(KeyCode::Space),
+            state: bevy::input::ButtonState::Pressed,
+            window: Entity::PLACEHOLDER,
+        });
+        app.update();
+        assert!(!low_power(&app));
+        assert!(matches!(app.world.resource::<WinitSettings>().focused_mode, UpdateMode::Continuous));
+    }
+
+    #[test]
+    fn themes_pick_visuals_and_take_the_accent() {
+        assert!(theme_visuals(Theme::Dark, None).dark_mode);
+        let light = theme_visuals(Theme::Light, None);
+        assert!(!light.dark_mode);
+        assert_eq!(light.selection.bg_fill, egui::Visuals::light().selection.bg_fill);
+        let accented = theme_visuals(Theme::Light, Some([200, 40, 90]));
+        assert_eq!(accented.selection.bg_fill, egui::Color32::from_rgb(200, 40, 90));
+        assert_eq!(accented.widgets.active.weak_bg_fill, egui::Color32::from_rgb(200, 40, 90));
+        assert_eq!(accented.panel_fill, light.panel_fill);
+    }
+
+    #[test]
+    fn win_progress_is_exact_near_the_goal() {
+        assert_eq!(win_progress(0, WIN_SCORE), 0.0);
+        assert_eq!(win_progress(372_000_000_000, WIN_SCORE), 0.372);
+        assert_eq!(win_progress(WIN_SCORE - 1, WIN_SCORE), 0.999_999);
+        assert_eq!(win_progress(u64::MAX, WIN_SCORE), 1.0);
+    }
+
+    #[test]
+    fn touches_past_the_first_press_buttons_themselves() {
+        let touch = |phase, id, x| TouchInput { phase, position: Vec2::new(x, 20.0), force: None, id };
+        let mut pointer = None;
+        // the first finger is egui's pointer, the other two come back here
+        let frame = [touch(TouchPhase::Started, 1, 10.0), touch(TouchPhase::Started, 2, 40.0), touch(TouchPhase::Started, 3, 80.0)];
+        assert_eq!(secondary_touches(&mut pointer, &frame, 2.0), vec![egui::pos2(20.0, 10.0), egui::pos2(40.0, 10.0)]);
+        assert_eq!(pointer, Some(1));
+
+        // lifting another finger leaves the pointer where it was
+        let frame = [touch(TouchPhase::Ended, 2, 40.0), touch(TouchPhase::Moved, 1, 12.0)];
+        assert!(secondary_touches(&mut pointer, &frame, 2.0).is_empty());
+        assert_eq!(pointer, Some(1));
+
+        // once the pointer's finger lifts the next touch takes over
+        let frame = [touch(TouchPhase::Ended, 1, 12.0), touch(TouchPhase::Started, 4, 60.0)];
+        assert!(secondary_touches(&mut pointer, &frame, 2.0).is_empty());
+        assert_eq!(pointer, Some(4));
+    }
+
+    #[test]
+    fn large_ui_scales_on_top_of_ui_scale() {
+        let settings = Settings { ui_scale: 1.2, ..Settings::default() };
+        assert_eq!(settings.ui_pixels_per_point(), 1.2);
+        assert_eq!(Settings { large_ui: true, ..settings }.ui_pixels_per_point(), 1.2 * LARGE_UI_SCALE);
+        assert!(ui_spacing(true).interact_size.y > ui_spacing(false).interact_size.y);
+    }
+
+    #[test]
+    fn win_eta_needs_a_rate() {
+        assert_eq!(win_eta(0, WIN_SCORE, 0.0), None);
+        assert_eq!(win_eta(WIN_SCORE - 252, WIN_SCORE, 1.0), Some(Duration::from_secs(252)));
+        assert_eq!(win_eta(WIN_SCORE, WIN_SCORE, 5.0), Some(Duration::ZERO));
+        assert_eq!(win_eta(0, WIN_SCORE, 1e-300), None);
+    }
+
+    #[test]
+    fn reminders_come_once_per_interval() {
+        let mins = |m: u64| Duration::from_secs(m * 60);
+        assert!(!reminder_due(mins(29), Duration::ZERO, 30));
+        assert!(reminder_due(mins(30), Duration::ZERO, 30));
+        assert!(!reminder_due(mins(31), mins(30), 30));
+        assert!(reminder_due(mins(60), mins(30), 30));
+        assert!(!reminder_due(mins(600), Duration::ZERO, 0));
+    }
+
+    #[test]
+    fn a_snoozed_reminder_comes_back_after_the_snooze() {
+        let mut app = crate::testing::manual_clock_app(Duration::from_secs(60));
+        app.init_resource::<UiState>()
+            .add_event::<ReminderAnswer>()
+            .add_systems(Update, (crate::economy::play_time_system, reminder_schedule_system).chain());
+        app.world.resource_mut::<Settings>().reminder_mins = 30;
+        let open_after = |app: &mut App, minutes| {
+            for _ in 0..minutes {
+                app.update();
+            }
+            app.world.resource::<UiState>().reminder_open
+        };
+        assert!(!open_after(&mut app, 29));
+        assert!(open_after(&mut app, 1));
+
+        // answered a minute in, so back five minutes after that
+        app.world.send_event(ReminderAnswer::Snooze);
+        assert!(!open_after(&mut app, 5));
+        assert!(open_after(&mut app, 1));
+        // a dismissal waits the whole interval from when it came up
+        app.world.send_event(ReminderAnswer::Dismiss);
+        assert!(!open_after(&mut app, 29));
+        assert!(open_after(&mut app, 1));
+    }
+
+    #[test]
+    fn hints_follow_progress_in_order() {
+        let hint = |progress: &Progress| next_hint(progress, 0).map(|step| HINT_RULES[step].0);
+        let mut progress = Progress { total_fingers: 1, hands: 1, buildings: 1, ..Progress::default() };
+        assert_eq!(hint(&progress), Some(Hint::Click));
+        progress.lifetime_clicks = 5;
+        assert_eq!(hint(&progress), Some(Hint::Finger));
+        progress.total_fingers = 4;
+        assert_eq!(hint(&progress), Some(Hint::Hand));
+        // skipping ahead doesn't bring back a step already taken
+        progress.hands = 2;
+        progress.combined = 1;
+        progress.autoed = 1;
+        assert_eq!(hint(&progress), Some(Hint::UpgradeFingers));
+        progress.finger_upgrades = 1;
+        assert_eq!(hint(&progress), Some(Hint::Cashout));
+        progress.buildings = 2;
+        assert_eq!(hint(&progress), None);
+    }
+
+    #[test]
+    fn hints_stay_done_after_a_cashout() {
+        let mut app = crate::testing::test_app();
+        app.init_resource::<UiState>().add_systems(Update, hint_progress_system);
+        let hint = |app: &App| app.world.resource::<UiState>().hint;
+        app.world.resource_mut::<Statistics>().lifetime_clicks = 1_000;
+        *app.world.resource_mut::<Score>() = Score(Economy { total_fingers: 10, finger_upgrades: 1, ..Economy::default() });
+        let hands: Vec<Entity> = [HandState::Filling, HandState::Autoed]
+            .into_iter()
+            .map(|state| app.world.spawn(state).id())
+            .collect();
+        app.update();
+        assert_eq!(hint(&app), Some(Hint::Cashout));
+
+        // back to one hand of one finger, with a building to show for it
+        for hand in hands {
+            app.world.despawn(hand);
+        }
+        app.world.spawn(HandState::Filling);
+        *app.world.resource_mut::<Score>() = Score(Economy { buildings: 2, ..Economy::default() });
+        app.update();
+        assert_eq!(hint(&app), None);
+        assert_eq!(app.world.resource::<Statistics>().hint_steps, HINT_RULES.len());
+    }
+
+    #[test]
+    fn restored_windows_are_clamped_on_screen() {
+        let screen = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(800.0, 600.0));
+        assert_eq!(clamp_to_screen(egui::pos2(100.0, 200.0), screen), egui::pos2(100.0, 200.0));
+        assert_eq!(clamp_to_screen(egui::pos2(1900.0, 1000.0), screen), egui::pos2(760.0, 560.0));
+        assert_eq!(clamp_to_screen(egui::pos2(-50.0, -10.0), screen), egui::pos2(0.0, 0.0));
+    }
+
+    #[test]
+    fn affordability_needs_clicks_and_a_hand_to_spend_on() {
+        let balance = Balance::default();
+        let mut score = Economy::default();
+        assert_eq!(affordable_items(&score, &balance, true, true), [false; 5]);
+        score.stored_clicks = u64::MAX;
+        assert_eq!(affordable_items(&score, &balance, true, true), [true; 5]);
+        assert_eq!(affordable_items(&score, &balance, false, false), [false, true, false, false, true]);
+    }
+
+    #[test]
+    fn focus_cycles_through_the_hands_and_wraps() {
+        let order = [Entity::from_raw(1), Entity::from_raw(2), Entity::from_raw(3)];
+        let mut focused = FocusedHand::default();
+        focused.cycle(&order, -1);
+        assert_eq!(focused.hand, Some(order[2]));
+        focused.cycle(&order, 1);
+        assert_eq!(focused.hand, Some(order[0]));
+        focused.cycle(&order, 1);
+        assert_eq!(focused.hand, Some(order[1]));
+        // a hand that's gone starts over
+        focused.cycle(&order[..1], 1);
+        assert_eq!(focused.hand, Some(order[0]));
+        focused.cycle(&[], 1);
+        assert_eq!(focused.hand, Some(order[0]));
+    }
+
+    #[test]
+    fn hands_are_tallied_and_badged_by_state() {
+        let states = [HandState::Autoed, HandState::Filling, HandState::Autoed, HandState::Combined, HandState::Autoed];
+        assert_eq!(HandTally::count(&states), HandTally { filling: 1, combined: 1, autoed: 3 });
+        assert_eq!(HandTally::count(&[]), HandTally::default());
+        let badges = [HandState::Filling, HandState::Combined, HandState::Autoed].map(hand_state_badge);
+        for (i, badge) in badges.iter().enumerate() {
+            assert!(badges[i + 1..].iter().all(|other| other.0 != badge.0 && other.1 != badge.1));
+        }
+    }
+}